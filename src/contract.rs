@@ -1,22 +1,51 @@
-use cosmwasm_std::{entry_point, Addr, CosmosMsg, Order, StdError};
+use std::collections::BTreeSet;
+
+use cosmwasm_std::{
+    entry_point, Addr, BankMsg, Coin, CosmosMsg, Empty, Order, QueryRequest, Reply, SubMsg,
+    WasmMsg, WasmQuery,
+};
 use cosmwasm_std::{
-    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
+    from_binary, to_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
+    StdResult, Storage, Timestamp, Uint128,
 };
 use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, parse_reply_instantiate_data, Duration, Expiration};
 
 use crate::error::ContractError;
+use crate::helpers::{authz_delegate_msg, query_token_metadata};
 use crate::msg::{
-    ConfigResponse, DripPoolResponse, DripPoolsResponse, DripTokensResponse, ExecuteMsg,
-    InstantiateMsg, ParticipantSharesResponse, ParticipantsResponse, QueryMsg, UncheckedDripToken,
+    AllowedDripToken, AllowedDripTokensResponse, AuthorizationResponse, ClaimableResponse,
+    ClaimableTokens, ConfigResponse, DripPoolResponse, DripPoolsResponse, DripTokensResponse,
+    ExchangeRateResponse, ExecuteMsg, InstantiateMsg, LinksResponse, LiquidStakingExecuteMsg,
+    LstSourcesResponse, MemberChangedHookMsg, MemberListResponse, MemberResponse,
+    ParticipantSharesResponse, ParticipantsResponse, PoolShareholdersResponse, QueryMsg,
+    ReceiptTokenInstantiateMsg, ReceiveMsg, ShareAllowanceResponse, Shareholder,
+    SimulateDistributionResponse, SimulatedDripPool, SimulatedParticipant,
+    StakedBalanceAtHeightResponse, UncheckedDripToken, UncheckedLstSource,
+    VestingPositionsResponse, VotingPowerAtHeightResponse, WeightSourceQueryMsg,
 };
 use crate::state::{
-    Config, DripPool, CONFIG, DRIP_POOLS, DRIP_TOKENS, PARTICIPANTS, PARTICIPANTS_SHARES,
+    participants_shares, Authorization, Config, DripPool, DripToken, EmissionSchedule, LstSource,
+    LstToken, PoolStatus, RedemptionRate, ShareAllowance, VestingPosition, WeightSource,
+    ALLOWED_DRIP_TOKENS, AUTHORIZATIONS, CLOSED_POOLS, CLOSED_POOLS_COUNT, CONFIG, DRIP_POOLS,
+    DRIP_TOKENS, LINKED_ADDRESSES, LST_SOURCES, PARTICIPANTS, PARTICIPANTS_INDEX, PENDING_LINKS,
+    PENDING_RECEIPT_TOKEN, POOL_CLOSINGS, PRIMARY_LINKS, RECEIPT_TOKENS, SETTLEMENT_CURSORS,
+    SHARE_ALLOWANCES, TOTAL_STAKE, VESTING_POSITIONS,
 };
 
 // Version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw-drip";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Pagination limits
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+// Reply id of the receipt token instantiation
+const RECEIPT_TOKEN_REPLY_ID: u64 = 1;
+
 //==================================================================================================
 // INSTANTIATE
 //==================================================================================================
@@ -32,20 +61,34 @@ pub fn instantiate(
 
     // The contract owner is forced to be the address who send the InstantiateMsg
     // this imposes the instantiation to be performed by the DAO. It will be the only
-    // address allowed to create drip pools
+    // address allowed to create drip pools unless sponsored pools are enabled
     let next_distribution_time = env.block.time.seconds() + msg.epoch_duration;
+    let liquid_staking = msg
+        .liquid_staking
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let weight_source = match msg.weight_source {
+        Some(weight_source) => weight_source.validate(deps.api)?,
+        None => WeightSource::NativeStaking {},
+    };
     let config = Config {
         owner: info.sender,
         min_staking_amount: msg.min_staking_amount,
         epoch_duration: msg.epoch_duration,
         next_distribution_time,
+        liquid_staking,
+        sponsorship: msg.sponsorship,
+        denom_metadata: msg.denom_metadata.unwrap_or(false),
+        weight_source,
+        receipt_token_code_id: msg.receipt_token_code_id,
     };
 
     CONFIG.save(deps.storage, &config)?;
 
     // Initialize other storages to use update on them later
-    PARTICIPANTS.save(deps.storage, &Vec::new())?;
+    TOTAL_STAKE.save(deps.storage, &Uint128::zero())?;
     DRIP_TOKENS.save(deps.storage, &Vec::new())?;
+    CLOSED_POOLS_COUNT.save(deps.storage, &0u64)?;
 
     Ok(Response::new()
         .add_attribute("action", "instantiate")
@@ -70,75 +113,245 @@ pub fn execute(
             token_info,
             tokens_per_epoch,
             epochs_number,
-        } => execute_create_drip_pool(deps, env, info, token_info, tokens_per_epoch, epochs_number),
+            vesting,
+            early_exit_penalty,
+            emission_schedule,
+            start_time,
+            end_time,
+        } => execute_create_drip_pool(
+            deps,
+            env,
+            info.sender,
+            Deposit::Native(info.funds),
+            token_info,
+            tokens_per_epoch,
+            epochs_number,
+            vesting,
+            early_exit_penalty,
+            emission_schedule,
+            start_time,
+            end_time,
+        ),
         ExecuteMsg::UpdateDripPool {} => todo!(),
-        ExecuteMsg::RemoveDripPool {} => todo!(),
+        ExecuteMsg::RemoveDripPool { token } => execute_remove_drip_pool(deps, info, token),
+        ExecuteMsg::PauseDripPool { token } => execute_pause_drip_pool(deps, info, token),
+        ExecuteMsg::ResumeDripPool { token } => execute_resume_drip_pool(deps, env, info, token),
+        ExecuteMsg::TopUpDripPool {
+            token,
+            additional_epochs,
+        } => {
+            let amount = must_pay(&info, &token)?;
+            execute_top_up_drip_pool(deps, env, info.sender, token, amount, additional_epochs)
+        }
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::AllowDripToken {
+            token,
+            min_initial_amount,
+        } => execute_allow_drip_token(deps, info, token, min_initial_amount),
+        ExecuteMsg::DisallowDripToken { token } => execute_disallow_drip_token(deps, info, token),
+        ExecuteMsg::AddLstSource { source } => execute_add_lst_source(deps, info, source),
+        ExecuteMsg::RemoveLstSource { token } => execute_remove_lst_source(deps, info, token),
         ExecuteMsg::DistributeShares {} => execute_distribute_shares(deps, env, info),
-        ExecuteMsg::SendShares {} => todo!(),
-        ExecuteMsg::WithdrawTokens {} => execute_withdraw_tokens(deps, env, info),
+        ExecuteMsg::SendShares {
+            recipient,
+            token,
+            amount,
+        } => execute_send_shares(deps, info, recipient, token, amount),
+        ExecuteMsg::IncreaseShareAllowance {
+            spender,
+            token,
+            amount,
+            expires,
+        } => execute_increase_share_allowance(deps, env, info, spender, token, amount, expires),
+        ExecuteMsg::DecreaseShareAllowance {
+            spender,
+            token,
+            amount,
+            expires,
+        } => execute_decrease_share_allowance(deps, env, info, spender, token, amount, expires),
+        ExecuteMsg::TransferSharesFrom {
+            owner,
+            recipient,
+            token,
+            amount,
+        } => execute_transfer_shares_from(deps, env, info, owner, recipient, token, amount),
+        ExecuteMsg::WithdrawTokens {
+            tokens,
+            shares,
+            owner,
+            recipient,
+            restake_to,
+        } => execute_withdraw_tokens(
+            deps,
+            env,
+            info,
+            tokens,
+            shares,
+            owner,
+            Destination::Transfer {
+                recipient,
+                restake_to,
+            },
+        ),
+        ExecuteMsg::WithdrawTokensAndCall {
+            contract,
+            msg,
+            tokens,
+            shares,
+            owner,
+        } => execute_withdraw_tokens(
+            deps,
+            env,
+            info,
+            tokens,
+            shares,
+            owner,
+            Destination::Call { contract, msg },
+        ),
+        ExecuteMsg::ClaimReceiptTokens {} => execute_claim_receipt_tokens(deps, info),
+        ExecuteMsg::Sync { address } => execute_sync(deps, info, address),
+        ExecuteMsg::LinkAddress { address } => execute_link_address(deps, info, address),
+        ExecuteMsg::ConfirmLink { primary } => execute_confirm_link(deps, info, primary),
+        ExecuteMsg::UnlinkAddress { address } => execute_unlink_address(deps, info, address),
+        ExecuteMsg::SyncParticipants { start_after, limit } => {
+            execute_sync_participants(deps, start_after, limit)
+        }
+        ExecuteMsg::SyncGroupMembers { start_after, limit } => {
+            execute_sync_group_members(deps, start_after, limit)
+        }
+        ExecuteMsg::MemberChangedHook(msg) => execute_member_changed_hook(deps, info, msg),
+        ExecuteMsg::Authorize {
+            operator,
+            expiry,
+            recipient,
+        } => execute_authorize(deps, env, info, operator, expiry, recipient),
+        ExecuteMsg::Revoke { operator } => execute_revoke(deps, info, operator),
+        ExecuteMsg::ClaimVested {} => execute_claim_vested(deps, env, info),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Participants {} => to_binary(&query_participants(deps)?),
+        QueryMsg::Participants { start_after, limit } => {
+            to_binary(&query_participants(deps, start_after, limit)?)
+        }
         QueryMsg::DripTokens {} => to_binary(&query_drip_tokens(deps)?),
-        QueryMsg::DripPool { token } => to_binary(&query_drip_pool(deps, token)?),
-        QueryMsg::DripPools {} => to_binary(&query_drip_pools(deps)?),
-        QueryMsg::ParticipantShares { address } => {
-            to_binary(&query_participant_shares(deps, address)?)
+        QueryMsg::DripPool { token } => to_binary(&query_drip_pool(deps, env, token)?),
+        QueryMsg::DripPools {
+            status,
+            start_after,
+            limit,
+        } => to_binary(&query_drip_pools(deps, env, status, start_after, limit)?),
+        QueryMsg::AllowedDripTokens {} => to_binary(&query_allowed_drip_tokens(deps)?),
+        QueryMsg::LstSources {} => to_binary(&query_lst_sources(deps)?),
+        QueryMsg::Links { address } => to_binary(&query_links(deps, address)?),
+        QueryMsg::ParticipantShares {
+            address,
+            start_after,
+            limit,
+        } => to_binary(&query_participant_shares(
+            deps,
+            address,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::PoolShareholders {
+            token,
+            start_after,
+            limit,
+        } => to_binary(&query_pool_shareholders(deps, token, start_after, limit)?),
+        QueryMsg::Claimable { address } => to_binary(&query_claimable(deps, address)?),
+        QueryMsg::SimulateDistribution { start_after, limit } => {
+            to_binary(&query_simulate_distribution(deps, env, start_after, limit)?)
+        }
+        QueryMsg::Authorization { owner, operator } => {
+            to_binary(&query_authorization(deps, owner, operator)?)
+        }
+        QueryMsg::ShareAllowance {
+            owner,
+            spender,
+            token,
+        } => to_binary(&query_share_allowance(deps, env, owner, spender, token)?),
+        QueryMsg::VestingPositions { address } => {
+            to_binary(&query_vesting_positions(deps, env, address)?)
         }
     }
 }
 
-/// Add the info.sender to the PARTICIPANTS vector or raise an error if it is already inside it
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        RECEIPT_TOKEN_REPLY_ID => reply_receipt_token(deps, msg),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+/// Save the address of the receipt token instantiated along with a drip pool
+fn reply_receipt_token(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let resp = parse_reply_instantiate_data(msg)?;
+    let receipt_token = deps.api.addr_validate(&resp.contract_address)?;
+
+    let token = PENDING_RECEIPT_TOKEN.load(deps.storage)?;
+    PENDING_RECEIPT_TOKEN.remove(deps.storage);
+    DRIP_POOLS.update(deps.storage, token.clone(), |drip_pool| -> StdResult<_> {
+        let mut drip_pool = drip_pool.unwrap();
+        drip_pool.receipt_token = Some(receipt_token.clone());
+        Ok(drip_pool)
+    })?;
+    RECEIPT_TOKENS.save(deps.storage, &receipt_token, &token)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "receipt_token")
+        .add_attribute("token", token)
+        .add_attribute("receipt_token", receipt_token))
+}
+
+/// Add the info.sender to the PARTICIPANTS map or raise an error if it is already inside it
 pub fn execute_add_participant(
-    deps: DepsMut,
+    mut deps: DepsMut,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let delegations = deps.querier.query_all_delegations(info.sender.clone())?;
 
-    PARTICIPANTS.update(deps.storage, |mut participants| {
-        if participants.contains(&info.sender) {
-            return Err(ContractError::AlreadyParticipant {});
-        }
+    if PARTICIPANTS.has(deps.storage, &info.sender) {
+        return Err(ContractError::AlreadyParticipant {});
+    }
 
-        // Compute total $JUNO delegated by current address
-        let eligible_delegations = delegations
-            .iter()
-            .map(|delegation| delegation.amount.amount)
-            .filter(|amount| amount >= &config.min_staking_amount)
-            .count();
+    // Linked addresses already count toward their primary participant
+    if LINKED_ADDRESSES.has(deps.storage, &info.sender) {
+        return Err(ContractError::AlreadyLinked {
+            address: info.sender.to_string(),
+        });
+    }
 
-        if eligible_delegations == 0 {
-            return Err(ContractError::MinimumDelegationNotSatisfied {
-                min_staked: config.min_staking_amount,
-            });
-        }
+    let total_staked = query_eligible_stake(deps.as_ref(), &config, &info.sender)?;
+    if total_staked.is_zero() {
+        return Err(ContractError::MinimumDelegationNotSatisfied {
+            min_staked: config.min_staking_amount,
+        });
+    }
 
-        participants.push(info.sender.clone());
-        Ok(participants)
-    })?;
+    // Shares accrue only from the current distribution index onward
+    settle_participant(&mut deps, &info.sender)?;
+    update_participant_stake(&mut deps, &info.sender, total_staked)?;
 
     let res = Response::new()
         .add_attribute("action", "add_participant")
-        .add_attribute("address", info.sender);
+        .add_attribute("address", info.sender)
+        .add_attribute("stake", total_staked);
     Ok(res)
 }
 
-/// Retains elements of the PARTICIPANTS vector that are different from the info.sender.
+/// Settle the info.sender shares and remove it from the PARTICIPANTS map.
 /// No check is made if the info.sender was a participant or not
 pub fn execute_remove_participant(
-    deps: DepsMut,
+    mut deps: DepsMut,
     info: MessageInfo,
 ) -> Result<Response, ContractError> {
-    PARTICIPANTS.update(deps.storage, |mut participants| -> StdResult<_> {
-        participants.retain(|address| *address != info.sender);
-        Ok(participants)
-    })?;
+    settle_participant(&mut deps, &info.sender)?;
+    update_participant_stake(&mut deps, &info.sender, Uint128::zero())?;
 
     let res = Response::new()
         .add_attribute("action", "remove_participant")
@@ -146,18 +359,171 @@ pub fn execute_remove_participant(
     Ok(res)
 }
 
+/// Settle the shares of an address, the sender by default, and refresh its stake. A
+/// participant whose delegations no longer satisfy the minimum staking is removed.
+pub fn execute_sync(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    address: Option<String>,
+) -> Result<Response, ContractError> {
+    let address = match address {
+        Some(address) => deps.api.addr_validate(&address)?,
+        None => info.sender,
+    };
+    let total_staked = sync_participant(&mut deps, &address)?;
+
+    let res = Response::new()
+        .add_attribute("action", "sync")
+        .add_attribute("address", address)
+        .add_attribute("stake", total_staked);
+    Ok(res)
+}
+
+/// Settle and refresh the stake of a page of participants. Participants no more eligible
+/// are removed.
+pub fn execute_sync_participants(
+    mut deps: DepsMut,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // Collect the page first since syncing can remove participants
+    let participants = PARTICIPANTS
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut removed = 0u32;
+    for address in participants.iter() {
+        if sync_participant(&mut deps, address)?.is_zero() {
+            removed += 1;
+        }
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "sync_participants")
+        .add_attribute("participants", participants.len().to_string())
+        .add_attribute("removed", removed.to_string());
+    // Start of the next page
+    if let Some(address) = participants.last() {
+        res = res.add_attribute("last_participant", address);
+    }
+    Ok(res)
+}
+
+/// Add a page of the cw4 group members to the participants with their current weight.
+/// Members linked to a primary participant refresh its stake instead. Participants whose
+/// weight is below the minimum staking amount are removed.
+pub fn execute_sync_group_members(
+    mut deps: DepsMut,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let group = match &config.weight_source {
+        WeightSource::Cw4Group { address } => address,
+        _ => return Err(ContractError::NoCw4Group {}),
+    };
+
+    let resp: MemberListResponse = deps.querier.query_wasm_smart(
+        group,
+        &WeightSourceQueryMsg::ListMembers { start_after, limit },
+    )?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "sync_group_members")
+        .add_attribute("members", resp.members.len().to_string());
+    for member in resp.members.iter() {
+        let address = deps.api.addr_validate(&member.addr)?;
+        apply_member_weight(&mut deps, &config, &address)?;
+    }
+    // Start of the next page
+    if let Some(member) = resp.members.last() {
+        res = res.add_attribute("last_member", member.addr.clone());
+    }
+    Ok(res)
+}
+
+/// Apply the weight changes notified by the cw4 group
+pub fn execute_member_changed_hook(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    msg: MemberChangedHookMsg,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if !matches!(&config.weight_source, WeightSource::Cw4Group { address } if *address == info.sender)
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for diff in msg.diffs.iter() {
+        let address = deps.api.addr_validate(&diff.key)?;
+        apply_member_weight(&mut deps, &config, &address)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "member_changed_hook")
+        .add_attribute("diffs", msg.diffs.len().to_string()))
+}
+
+/// Settle the shares of a cw4 group member and account its new weight along with the
+/// weight of its linked addresses. The weight of a linked address is accounted to its
+/// primary participant instead. Participants below the minimum staking amount are
+/// removed.
+fn apply_member_weight(deps: &mut DepsMut, config: &Config, address: &Addr) -> StdResult<()> {
+    let participant = match LINKED_ADDRESSES.may_load(deps.storage, address)? {
+        Some(primary) => primary,
+        None => address.clone(),
+    };
+    settle_participant(deps, &participant)?;
+    let stake = query_eligible_stake(deps.as_ref(), config, &participant)?;
+    update_participant_stake(deps, &participant, stake)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn execute_create_drip_pool(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    sender: Addr,
+    deposit: Deposit,
     token_info: UncheckedDripToken,
     tokens_per_epoch: Uint128,
     epochs_number: u64,
+    vesting: Option<Duration>,
+    early_exit_penalty: Option<Decimal>,
+    emission_schedule: Option<EmissionSchedule>,
+    start_time: Option<Timestamp>,
+    end_time: Option<Timestamp>,
 ) -> Result<Response, ContractError> {
-    // Only owner can create drip pools
+    // Only owner can create drip pools unless sponsored pools are allowed
     let config = CONFIG.load(deps.storage)?;
-    if config.owner != info.sender {
-        return Err(ContractError::Unauthorized {});
+    let sponsored = config.owner != sender;
+    let creation_fee = if sponsored {
+        let sponsorship = config.sponsorship.ok_or(ContractError::Unauthorized {})?;
+        let initial_amount = token_info.initial_amount();
+        if initial_amount < sponsorship.min_initial_amount {
+            return Err(ContractError::DripPoolTooSmall {
+                min_initial_amount: sponsorship.min_initial_amount,
+            });
+        }
+        // Sponsors have to deposit the whole pool amount plus the fee
+        let creation_fee = initial_amount * sponsorship.creation_fee;
+        let expected = initial_amount + creation_fee;
+        let received = deposit.amount_of(&token_info.token());
+        if received != expected {
+            return Err(ContractError::WrongDeposit { expected, received });
+        }
+        creation_fee
+    } else {
+        Uint128::zero()
     };
 
     // At least on epoch pool
@@ -165,13 +531,35 @@ pub fn execute_create_drip_pool(
         return Err(ContractError::LessThanOneEpoch {});
     }
 
+    if matches!(vesting, Some(Duration::Height(0)) | Some(Duration::Time(0))) {
+        return Err(ContractError::ZeroVestingDuration {});
+    }
+
+    if matches!(early_exit_penalty, Some(penalty) if penalty >= Decimal::one()) {
+        return Err(ContractError::InvalidEarlyExitPenalty {});
+    }
+
+    // The pool must be able to distribute at least once
+    if let Some(end_time) = end_time {
+        if end_time <= env.block.time || matches!(start_time, Some(start) if end_time <= start) {
+            return Err(ContractError::InvalidEndTime {});
+        }
+    }
+
+    let status = match start_time {
+        Some(start_time) if env.block.time < start_time => PoolStatus::Pending,
+        _ => PoolStatus::Active,
+    };
+
+    let contract_address = env.contract.address.clone();
+
     // Basic checks on token
-    let drip_token = token_info.validate(deps.as_ref(), env)?;
+    let drip_token = token_info.validate(deps.as_ref(), env, sponsored)?;
+    let metadata = query_token_metadata(deps.as_ref(), &drip_token, config.denom_metadata)?;
 
     // Required amount for the drip
-    let total_drip_amount = tokens_per_epoch
-        .checked_mul(epochs_number.into())
-        .map_err(StdError::overflow)?;
+    let emission_schedule = emission_schedule.unwrap_or(EmissionSchedule::Constant {});
+    let total_drip_amount = emission_schedule.total_amount(tokens_per_epoch, epochs_number)?;
 
     let available_amount = drip_token.get_available_amount();
 
@@ -183,20 +571,31 @@ pub fn execute_create_drip_pool(
     }
 
     // Check if drip pool exists or create it
-    DRIP_POOLS.update(deps.storage, drip_token.clone().get_token(), |drip_pool| {
-        if drip_pool.is_some() {
-            return Err(ContractError::DripPoolAlreadyExists {});
-        }
-        Ok(DripPool {
-            drip_token: drip_token.clone(),
-            initial_amount: drip_token.get_available_amount(),
-            tokens_per_epoch,
-            withdrawable_tokens: Uint128::zero(),
-            issued_shares: Uint128::zero(),
-            epochs_number,
-            epoch: 0u64,
-        })
-    })?;
+    let drip_pool =
+        DRIP_POOLS.update(deps.storage, drip_token.clone().get_token(), |drip_pool| {
+            if drip_pool.is_some() {
+                return Err(ContractError::DripPoolAlreadyExists {});
+            }
+            Ok(DripPool {
+                drip_token: drip_token.clone(),
+                initial_amount: drip_token.get_available_amount(),
+                tokens_per_epoch,
+                withdrawable_tokens: Uint128::zero(),
+                issued_shares: Uint128::zero(),
+                epochs_number,
+                epoch: 0u64,
+                shares_per_stake: Uint128::zero(),
+                vesting,
+                early_exit_penalty,
+                emission_schedule: emission_schedule.clone(),
+                start_time,
+                end_time,
+                status,
+                creator: sender.clone(),
+                metadata: metadata.clone(),
+                receipt_token: None,
+            })
+        })?;
 
     // Add token to the list of active pools
     DRIP_TOKENS.update(deps.storage, |mut drip_tokens| -> StdResult<_> {
@@ -204,11 +603,42 @@ pub fn execute_create_drip_pool(
         Ok(drip_tokens)
     })?;
 
-    let res = Response::new()
+    let mut res = Response::new()
         .add_attribute("action", "add_drip_pool")
         .add_attribute("token", drip_token.get_token())
+        .add_attribute("creator", sender)
         .add_attribute("amount", drip_token.get_available_amount())
         .add_attribute("epochs_number", epochs_number.to_string());
+    if !creation_fee.is_zero() {
+        res = res
+            .add_message(drip_pool.send_tokens_message(creation_fee, &config.owner)?)
+            .add_attribute("creation_fee", creation_fee);
+    }
+
+    // The receipt token address is saved on reply
+    if let Some(code_id) = config.receipt_token_code_id {
+        PENDING_RECEIPT_TOKEN.save(deps.storage, &drip_token.get_token())?;
+        let instantiate_msg = ReceiptTokenInstantiateMsg {
+            name: "Drip pool shares".to_string(),
+            symbol: "DRIP".to_string(),
+            decimals: 6,
+            initial_balances: vec![],
+            mint: Some(MinterResponse {
+                minter: contract_address.to_string(),
+                cap: None,
+            }),
+        };
+        res = res.add_submessage(SubMsg::reply_on_success(
+            WasmMsg::Instantiate {
+                admin: Some(contract_address.to_string()),
+                code_id,
+                msg: to_binary(&instantiate_msg)?,
+                funds: vec![],
+                label: format!("drip shares {}", drip_token.get_token()),
+            },
+            RECEIPT_TOKEN_REPLY_ID,
+        ));
+    }
     Ok(res)
 }
 
@@ -225,201 +655,1876 @@ fn execute_distribute_shares(
         return Err(ContractError::ZeroActiveDripPool {});
     }
 
-    // Initialize shares emitted. Shares emitted will be equal to the sum of the staked tokens
-    // of all eligible participants
-    let mut emitted_shares = Uint128::zero();
-
     // Check if pay time!
-    if config.next_distribution_time <= env.block.time.seconds() {
-        let participants = PARTICIPANTS.load(deps.storage)?;
+    if config.next_distribution_time > env.block.time.seconds() {
+        return Err(ContractError::NoDistributionTime {});
+    }
+
+    // Liquid staking tokens can move between participants at any time, so the stake of
+    // every participant is refreshed to count each balance only once
+    if lst_stake_counted(deps.as_ref(), &config) {
+        let participants = PARTICIPANTS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for participant in participants.iter() {
+            sync_participant(&mut deps, participant)?;
+        }
+    }
+
+    // Shares emitted will be equal to the sum of the stake of all participants
+    // accounted at their last settlement
+    let emitted_shares = TOTAL_STAKE.load(deps.storage)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "distribute shares")
+        .add_attribute("emitted shares per pool", emitted_shares);
+
+    // Update pools
+    let (tokens_to_retain, msgs) = update_drip_pools(&mut deps, &env, drip_tokens, emitted_shares)?;
+    res = res.add_messages(msgs);
+
+    // Update drip tokens vector removing expired pool
+    DRIP_TOKENS.update(deps.storage, |_| -> StdResult<_> { Ok(tokens_to_retain) })?;
+
+    // Update new distribution time
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+        let time_after_epoch = env.block.time.seconds() - config.next_distribution_time;
+        let quotient = time_after_epoch / config.epoch_duration;
+        config.next_distribution_time += quotient * config.epoch_duration;
+        Ok(config)
+    })?;
+
+    Ok(res)
+}
 
-        for participant in participants {
-            let delegations = deps.querier.query_all_delegations(participant.clone())?;
+/// Whether liquid staking token balances count as stake. Since these tokens can be
+/// transferred instantly, the stake of every participant is refreshed by distributions.
+fn lst_stake_counted(deps: Deps, config: &Config) -> bool {
+    matches!(config.weight_source, WeightSource::NativeStaking {})
+        && !LST_SOURCES.is_empty(deps.storage)
+}
+
+/// Compute the weight of a participant summing the weight of its address and of all the
+/// addresses linked to it.
+pub fn query_eligible_stake(deps: Deps, config: &Config, address: &Addr) -> StdResult<Uint128> {
+    let mut stake = query_address_stake(deps, config, address)?;
+    for linked in PRIMARY_LINKS
+        .prefix(address)
+        .keys(deps.storage, None, None, Order::Ascending)
+    {
+        stake += query_address_stake(deps, config, &linked?)?;
+    }
+    Ok(stake)
+}
 
-            // Compute total $JUNO delegated by current address
-            let total_staked: Uint128 = delegations
+/// Compute the weight of an address from the configured source. With native staking only
+/// delegations greater or equal to the minimum staking amount are considered, while other
+/// sources must provide at least the minimum staking amount.
+pub fn query_address_stake(deps: Deps, config: &Config, address: &Addr) -> StdResult<Uint128> {
+    let weight = match &config.weight_source {
+        WeightSource::NativeStaking {} => {
+            let delegations = deps.querier.query_all_delegations(address)?;
+            let mut stakes = delegations
                 .iter()
                 .map(|delegation| delegation.amount.amount)
-                .filter(|amount| amount >= &config.min_staking_amount)
-                .sum();
-
-            if total_staked != Uint128::zero() {
-                update_participant_shares(
-                    &mut deps,
-                    &participant,
-                    drip_tokens.clone(),
-                    total_staked,
-                )?;
-                emitted_shares += total_staked;
-            } else {
-                PARTICIPANTS.update(deps.storage, |mut participants| -> StdResult<_> {
-                    participants.retain(|address| *address != participant);
-                    Ok(participants)
-                })?;
+                .collect::<Vec<_>>();
+            // Liquid staking token balances count as delegations
+            for lst_source in LST_SOURCES.range(deps.storage, None, None, Order::Ascending) {
+                let (_, lst_source) = lst_source?;
+                stakes.push(query_lst_stake(deps, &lst_source, address)?);
             }
+            return Ok(stakes
+                .into_iter()
+                .filter(|amount| amount >= &config.min_staking_amount)
+                .sum());
+        }
+        WeightSource::DaoVotingModule { address: module } => {
+            let resp: VotingPowerAtHeightResponse = deps.querier.query_wasm_smart(
+                module,
+                &WeightSourceQueryMsg::VotingPowerAtHeight {
+                    address: address.to_string(),
+                    height: None,
+                },
+            )?;
+            resp.power
+        }
+        WeightSource::Cw20Stake { address: stake } => {
+            let resp: StakedBalanceAtHeightResponse = deps.querier.query_wasm_smart(
+                stake,
+                &WeightSourceQueryMsg::StakedBalanceAtHeight {
+                    address: address.to_string(),
+                    height: None,
+                },
+            )?;
+            resp.balance
+        }
+        WeightSource::Cw4Group { address: group } => {
+            let resp: MemberResponse = deps.querier.query_wasm_smart(
+                group,
+                &WeightSourceQueryMsg::Member {
+                    addr: address.to_string(),
+                    at_height: None,
+                },
+            )?;
+            Uint128::from(resp.weight.unwrap_or_default())
+        }
+    };
+
+    if weight < config.min_staking_amount {
+        return Ok(Uint128::zero());
+    }
+    Ok(weight)
+}
+
+/// Convert the liquid staking token balance of an address to native tokens
+pub fn query_lst_stake(deps: Deps, lst_source: &LstSource, address: &Addr) -> StdResult<Uint128> {
+    let balance = match &lst_source.token {
+        LstToken::Native { denom } => deps.querier.query_balance(address, denom)?.amount,
+        LstToken::Cw20 { address: token } => {
+            let resp: cw20::BalanceResponse = deps.querier.query_wasm_smart(
+                token,
+                &cw20::Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )?;
+            resp.balance
         }
+    };
+    if balance.is_zero() {
+        return Ok(balance);
+    }
 
-        // Update pools
-        let tokens_to_retain = update_drip_pools(&mut deps, drip_tokens, emitted_shares)?;
+    let rate = match &lst_source.rate {
+        RedemptionRate::Fixed { rate } => *rate,
+        RedemptionRate::Query { contract, msg } => {
+            let resp: ExchangeRateResponse =
+                deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: contract.to_string(),
+                    msg: msg.clone(),
+                }))?;
+            resp.exchange_rate
+        }
+    };
+    Ok(balance * rate)
+}
 
-        // Update drip tokens vector removing expired pool
-        DRIP_TOKENS.update(deps.storage, |_| -> StdResult<_> { Ok(tokens_to_retain) })?;
+/// Compute the shares accrued by an address since its last settlement. Only dripping pools
+/// and pools closed after the last settlement can have accrued shares.
+/// Returns the drip token, the pending shares and the pool index to snapshot.
+pub fn pending_shares(deps: Deps, address: &Addr) -> StdResult<Vec<(String, Uint128, Uint128)>> {
+    let stake = PARTICIPANTS
+        .may_load(deps.storage, address)?
+        .unwrap_or_default();
 
-        // Update new distribution time
-        CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
-            let time_after_epoch = env.block.time.seconds() - config.next_distribution_time;
-            let quotient = time_after_epoch / config.epoch_duration;
-            config.next_distribution_time += quotient * config.epoch_duration;
-            Ok(config)
-        })?;
-    } else {
-        return Err(ContractError::NoDistributionTime {});
+    let mut drip_tokens: BTreeSet<String> = DRIP_TOKENS.load(deps.storage)?.into_iter().collect();
+    if let Some(cursor) = SETTLEMENT_CURSORS.may_load(deps.storage, address)? {
+        for item in CLOSED_POOLS.range(
+            deps.storage,
+            Some(Bound::inclusive(cursor)),
+            None,
+            Order::Ascending,
+        ) {
+            let (_, drip_token) = item?;
+            drip_tokens.insert(drip_token);
+        }
     }
 
-    let res = Response::new()
-        .add_attribute("action", "distribute shares")
-        .add_attribute("emitted shares per pool", emitted_shares);
-    Ok(res)
+    drip_tokens
+        .into_iter()
+        .map(|token| {
+            let drip_pool = DRIP_POOLS.load(deps.storage, token.clone())?;
+            let shares = accrued_shares(deps, address, stake, &token, &drip_pool)?;
+            Ok((token, shares, drip_pool.shares_per_stake))
+        })
+        .collect()
 }
 
-/// Update the participant active pools shares based on staked amount.
-pub fn update_participant_shares(
-    deps: &mut DepsMut,
-    participant: &Addr,
-    drip_tokens: Vec<String>,
-    total_staked: Uint128,
-) -> Result<(), ContractError> {
-    for drip_token in drip_tokens {
-        PARTICIPANTS_SHARES.update(
+/// Shares accrued by an address with the given stake in a drip pool since its last
+/// settlement
+fn accrued_shares(
+    deps: Deps,
+    address: &Addr,
+    stake: Uint128,
+    token: &str,
+    drip_pool: &DripPool,
+) -> StdResult<Uint128> {
+    let last_index = last_index(deps, address, token)?;
+    Ok(stake.checked_mul(drip_pool.shares_per_stake - last_index)?)
+}
+
+/// Drip pool index observed by an address at its last settlement. Pools not snapshotted were
+/// closed at that time, and then their index is the one of their latest closing, or were
+/// created afterwards.
+fn last_index(deps: Deps, address: &Addr, token: &str) -> StdResult<Uint128> {
+    if let Some(index) = PARTICIPANTS_INDEX.may_load(deps.storage, (address, token.to_string()))? {
+        return Ok(index);
+    }
+    let cursor = match SETTLEMENT_CURSORS.may_load(deps.storage, address)? {
+        Some(cursor) => cursor,
+        None => return Ok(Uint128::zero()),
+    };
+    Ok(POOL_CLOSINGS
+        .prefix(token.to_string())
+        .range(
             deps.storage,
-            (participant, drip_token),
-            |shares| -> StdResult<_> {
-                shares.map_or(Ok(total_staked), |mut amount| {
-                    amount += total_staked;
-                    Ok(amount)
-                })
-            },
-        )?;
+            None,
+            Some(Bound::exclusive(cursor)),
+            Order::Descending,
+        )
+        .next()
+        .transpose()?
+        .map(|(_, index)| index)
+        .unwrap_or_default())
+}
+
+/// Record a drip pool that stopped dripping. Addresses settle it once more at their next
+/// settlement and skip it afterwards.
+fn close_drip_pool(storage: &mut dyn Storage, token: &str, index: Uint128) -> StdResult<()> {
+    let sequence = CLOSED_POOLS_COUNT.load(storage)?;
+    CLOSED_POOLS.save(storage, sequence, &token.to_string())?;
+    POOL_CLOSINGS.save(storage, (token.to_string(), sequence), &index)?;
+    CLOSED_POOLS_COUNT.save(storage, &(sequence + 1))
+}
+
+/// Settled and pending shares of an address in the drip pools following `start_after`.
+/// Pools without shares are skipped, so every drip pool after `start_after` may be read
+/// to fill a page.
+fn participant_shares<'a>(
+    deps: Deps<'a>,
+    address: &'a Addr,
+    start_after: Option<String>,
+) -> StdResult<impl Iterator<Item = StdResult<(String, Uint128)>> + 'a> {
+    let stake = PARTICIPANTS
+        .may_load(deps.storage, address)?
+        .unwrap_or_default();
+
+    Ok(DRIP_POOLS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .map(move |item| {
+            let (token, drip_pool) = item?;
+            let settled = participants_shares()
+                .may_load(deps.storage, (token.clone(), address))?
+                .unwrap_or_default();
+            let pending = accrued_shares(deps, address, stake, &token, &drip_pool)?;
+            Ok((token, settled + pending))
+        })
+        .filter(|item| !matches!(item, Ok((_, shares)) if shares.is_zero())))
+}
+
+/// Move the pending shares of an address into the participants shares and snapshot the
+/// current index of the drip pools that can still accrue shares.
+pub fn settle_participant(deps: &mut DepsMut, address: &Addr) -> StdResult<()> {
+    for (drip_token, shares, index) in pending_shares(deps.as_ref(), address)? {
+        if !shares.is_zero() {
+            participants_shares().update(
+                deps.storage,
+                (drip_token.clone(), address),
+                |amount| -> StdResult<_> { Ok(amount.unwrap_or_default() + shares) },
+            )?;
+        }
+        PARTICIPANTS_INDEX.save(deps.storage, (address, drip_token), &index)?;
     }
+    let cursor = CLOSED_POOLS_COUNT.load(deps.storage)?;
+    SETTLEMENT_CURSORS.save(deps.storage, address, &cursor)?;
     Ok(())
 }
 
-pub fn update_drip_pools(
+/// Replace the stake accounted for an address keeping TOTAL_STAKE consistent.
+/// A zero stake removes the address from the participants.
+pub fn update_participant_stake(
     deps: &mut DepsMut,
-    drip_tokens: Vec<String>,
-    emitted_shares: Uint128,
-) -> Result<Vec<String>, ContractError> {
-    let mut tokens_to_retain: Vec<String> = vec![];
-    // Only token in the drip tokens vector are associated to active pools.
-    for drip_token in drip_tokens {
-        DRIP_POOLS.update(deps.storage, drip_token.clone(), |drip_pool| {
-            // Drip pool has been initialized during pool creation so .unwrap() should be ok
-            let mut drip_pool = drip_pool.unwrap();
-
-            if drip_pool.epoch > drip_pool.epochs_number {
-                return Err(ContractError::InvalidActiveDripPool {});
-            }
+    address: &Addr,
+    stake: Uint128,
+) -> StdResult<()> {
+    let old_stake = PARTICIPANTS
+        .may_load(deps.storage, address)?
+        .unwrap_or_default();
 
-            if drip_pool.drip_token.get_available_amount() < drip_pool.tokens_per_epoch {
-                return Err(ContractError::DripPoolHasNotENoughFunds);
-            }
+    TOTAL_STAKE.update(deps.storage, |total_stake| -> StdResult<_> {
+        Ok(total_stake - old_stake + stake)
+    })?;
 
-            drip_pool.issued_shares += emitted_shares;
-            drip_pool.remove_available_tokens(drip_pool.tokens_per_epoch);
-            drip_pool.withdrawable_tokens += drip_pool.tokens_per_epoch;
-            drip_pool.epoch += 1;
+    if stake.is_zero() {
+        PARTICIPANTS.remove(deps.storage, address);
+    } else {
+        PARTICIPANTS.save(deps.storage, address, &stake)?;
+    }
+    Ok(())
+}
 
-            if drip_pool.epoch < drip_pool.epochs_number {
-                tokens_to_retain.push(drip_token.clone());
-            }
+/// Settle the shares of an address and, if it is a participant, refresh its stake.
+/// Returns the stake accounted from now on.
+pub fn sync_participant(deps: &mut DepsMut, address: &Addr) -> StdResult<Uint128> {
+    settle_participant(deps, address)?;
 
-            Ok(drip_pool)
-        })?;
+    if !PARTICIPANTS.has(deps.storage, address) {
+        return Ok(Uint128::zero());
     }
-    Ok(tokens_to_retain)
+
+    let config = CONFIG.load(deps.storage)?;
+    let total_staked = query_eligible_stake(deps.as_ref(), &config, address)?;
+    update_participant_stake(deps, address, total_staked)?;
+    Ok(total_staked)
 }
 
-fn execute_withdraw_tokens(
+/// Cancel a drip pool refunding the tokens not yet distributed to its creator. Distributed
+/// tokens can still be withdrawn by shareholders.
+pub fn execute_remove_drip_pool(
     deps: DepsMut,
-    _env: Env,
     info: MessageInfo,
+    token: String,
 ) -> Result<Response, ContractError> {
-    let res: StdResult<Vec<_>> = PARTICIPANTS_SHARES
-        .prefix(&info.sender)
-        .range(deps.storage, None, None, Order::Ascending)
-        .collect();
-    let participant_shares = res?;
+    let mut drip_pool = DRIP_POOLS.may_load(deps.storage, token.clone())?.ok_or(
+        ContractError::DripPoolNotFound {
+            token: token.clone(),
+        },
+    )?;
 
-    if participant_shares.is_empty() {
-        return Err(ContractError::NoTokensToWithdraw {});
-    }
+    // Only the creator can cancel the pool
+    if drip_pool.creator != info.sender {
+        return Err(ContractError::Unauthorized {});
+    };
 
-    let mut send_msgs: Vec<CosmosMsg> = vec![];
-    for (token, shares) in participant_shares {
-        let mut pool = DRIP_POOLS.load(deps.storage, token.clone()).unwrap();
-        let withdraw_amount = pool.tokens_from_shares(shares);
-        pool.remove_tokens_and_shares(shares);
-        send_msgs.push(pool.send_tokens_message(withdraw_amount, &info.sender)?);
-        DRIP_POOLS.save(deps.storage, token.clone(), &pool)?;
-        PARTICIPANTS_SHARES.remove(deps.storage, (&info.sender, token));
+    if !drip_pool.is_dripping() {
+        return Err(ContractError::InactiveDripPool { token });
     }
 
-    let res = Response::new()
-        .add_attribute("action", "withdraw_tokens")
-        .add_messages(send_msgs);
-    Ok(res)
-}
+    let refund_amount = drip_pool.drip_token.get_available_amount();
+    drip_pool.remove_available_tokens(refund_amount);
+    drip_pool.status = PoolStatus::Cancelled;
+    DRIP_POOLS.save(deps.storage, token.clone(), &drip_pool)?;
+    close_drip_pool(deps.storage, &token, drip_pool.shares_per_stake)?;
 
-//==================================================================================================
-// QUERY
-//==================================================================================================
+    DRIP_TOKENS.update(deps.storage, |mut drip_tokens| -> StdResult<_> {
+        drip_tokens.retain(|drip_token| *drip_token != token);
+        Ok(drip_tokens)
+    })?;
 
-pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
-    Ok(ConfigResponse {
-        config: CONFIG.load(deps.storage)?,
-    })
+    let mut res = Response::new()
+        .add_attribute("action", "remove_drip_pool")
+        .add_attribute("token", token)
+        .add_attribute("refund", refund_amount);
+    if !refund_amount.is_zero() {
+        res = res.add_message(drip_pool.send_tokens_message(refund_amount, &drip_pool.creator)?);
+    }
+    Ok(res)
 }
 
-fn query_drip_tokens(deps: Deps) -> StdResult<DripTokensResponse> {
-    Ok(DripTokensResponse {
-        drip_tokens: DRIP_TOKENS.load(deps.storage)?,
-    })
-}
+/// Temporarily stop a drip pool. The pool is skipped by distributions without consuming
+/// epochs and its shares cannot be withdrawn until resumed.
+pub fn execute_pause_drip_pool(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    };
 
-fn query_participants(deps: Deps) -> StdResult<ParticipantsResponse> {
-    Ok(ParticipantsResponse {
-        participants: PARTICIPANTS.load(deps.storage)?,
-    })
-}
+    let mut drip_pool = DRIP_POOLS.may_load(deps.storage, token.clone())?.ok_or(
+        ContractError::DripPoolNotFound {
+            token: token.clone(),
+        },
+    )?;
 
-fn query_drip_pool(deps: Deps, token: String) -> StdResult<DripPoolResponse> {
-    let drip_pool = DRIP_POOLS.may_load(deps.storage, token)?;
-    match drip_pool {
-        Some(drip_pool) => Ok(DripPoolResponse {
-            drip_pool: Some(drip_pool),
-        }),
-        None => Ok(DripPoolResponse { drip_pool: None }),
+    match drip_pool.status {
+        PoolStatus::Pending | PoolStatus::Active => {}
+        PoolStatus::Paused => return Err(ContractError::PausedDripPool { token }),
+        _ => return Err(ContractError::InactiveDripPool { token }),
     }
-}
 
-fn query_drip_pools(deps: Deps) -> StdResult<DripPoolsResponse> {
-    let drip_tokens = DRIP_TOKENS.load(deps.storage)?;
-    let drip_pools = drip_tokens
-        .into_iter()
-        .map(|token| {
-            let drip_pool = DRIP_POOLS.load(deps.storage, token)?;
-            Ok(drip_pool)
-        })
-        .collect::<StdResult<Vec<DripPool>>>()?;
-    Ok(DripPoolsResponse { drip_pools })
+    drip_pool.status = PoolStatus::Paused;
+    DRIP_POOLS.save(deps.storage, token.clone(), &drip_pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "pause_drip_pool")
+        .add_attribute("token", token))
 }
 
-fn query_participant_shares(deps: Deps, address: String) -> StdResult<ParticipantSharesResponse> {
-    let address = &deps.api.addr_validate(&address)?;
-    let resp: StdResult<Vec<_>> = PARTICIPANTS_SHARES
-        .prefix(address)
-        .range(deps.storage, None, None, Order::Ascending)
-        .collect();
-    let shares = resp?;
+/// Restart a paused drip pool from the epoch at which it was paused
+pub fn execute_resume_drip_pool(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    let mut drip_pool = DRIP_POOLS.may_load(deps.storage, token.clone())?.ok_or(
+        ContractError::DripPoolNotFound {
+            token: token.clone(),
+        },
+    )?;
+
+    if drip_pool.status != PoolStatus::Paused {
+        return Err(ContractError::NotPausedDripPool { token });
+    }
+
+    drip_pool.status = if drip_pool.is_pending(&env.block.time) {
+        PoolStatus::Pending
+    } else {
+        PoolStatus::Active
+    };
+    DRIP_POOLS.save(deps.storage, token.clone(), &drip_pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "resume_drip_pool")
+        .add_attribute("token", token))
+}
+
+/// Allow sponsors to create drip pools of a token, optionally with a minimum initial amount
+pub fn execute_allow_drip_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+    min_initial_amount: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    ALLOWED_DRIP_TOKENS.save(deps.storage, token.clone(), &min_initial_amount)?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "allow_drip_token")
+        .add_attribute("token", token);
+    if let Some(min_initial_amount) = min_initial_amount {
+        res = res.add_attribute("min_initial_amount", min_initial_amount);
+    }
+    Ok(res)
+}
+
+/// Remove a token from the ones sponsors can distribute. Existing pools are left untouched.
+pub fn execute_disallow_drip_token(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    ALLOWED_DRIP_TOKENS.remove(deps.storage, token.clone());
+
+    Ok(Response::new()
+        .add_attribute("action", "disallow_drip_token")
+        .add_attribute("token", token))
+}
+
+/// Count the balances of a liquid staking token as native stake. Participants stake is
+/// refreshed at their next settlement.
+pub fn execute_add_lst_source(
+    deps: DepsMut,
+    info: MessageInfo,
+    source: UncheckedLstSource,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    let lst_source = source.validate(deps.api)?;
+    let token = lst_source.token.get_token();
+    LST_SOURCES.save(deps.storage, token.clone(), &lst_source)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_lst_source")
+        .add_attribute("token", token))
+}
+
+/// Stop counting the balances of a liquid staking token as native stake
+pub fn execute_remove_lst_source(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    LST_SOURCES.remove(deps.storage, token.clone());
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_lst_source")
+        .add_attribute("token", token))
+}
+
+/// Handle cw20 tokens sent to the contract
+pub fn execute_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let sender = deps.api.addr_validate(&wrapper.sender)?;
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::CreateDripPool {
+            initial_amount,
+            tokens_per_epoch,
+            epochs_number,
+            vesting,
+            early_exit_penalty,
+            emission_schedule,
+            start_time,
+            end_time,
+        } => execute_create_drip_pool(
+            deps,
+            env,
+            sender,
+            Deposit::Cw20 {
+                address: info.sender.clone(),
+                amount: wrapper.amount,
+            },
+            UncheckedDripToken::Cw20 {
+                address: info.sender.to_string(),
+                initial_amount,
+            },
+            tokens_per_epoch,
+            epochs_number,
+            vesting,
+            early_exit_penalty,
+            emission_schedule,
+            start_time,
+            end_time,
+        ),
+        ReceiveMsg::TopUpDripPool { additional_epochs } => execute_top_up_drip_pool(
+            deps,
+            env,
+            sender,
+            info.sender.to_string(),
+            wrapper.amount,
+            additional_epochs,
+        ),
+        ReceiveMsg::WithdrawTokens {
+            recipient,
+            restake_to,
+        } => {
+            let token = RECEIPT_TOKENS.may_load(deps.storage, &info.sender)?.ok_or(
+                ContractError::NotReceiptToken {
+                    address: info.sender.to_string(),
+                },
+            )?;
+            // Received receipt tokens are held by the contract like the settled shares
+            participants_shares().update(
+                deps.storage,
+                (token.clone(), &sender),
+                |shares| -> StdResult<_> { Ok(shares.unwrap_or_default() + wrapper.amount) },
+            )?;
+            execute_withdraw_tokens(
+                deps,
+                env,
+                MessageInfo {
+                    sender,
+                    funds: vec![],
+                },
+                Some(vec![token]),
+                Some(wrapper.amount),
+                None,
+                Destination::Transfer {
+                    recipient,
+                    restake_to,
+                },
+            )
+        }
+    }
+}
+
+/// Extend a constant emission drip pool by some epochs. The deposited amount must cover
+/// `tokens_per_epoch` for every additional epoch. A finished pool starts dripping again
+/// from the next distribution.
+pub fn execute_top_up_drip_pool(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    token: String,
+    amount: Uint128,
+    additional_epochs: u64,
+) -> Result<Response, ContractError> {
+    if additional_epochs < 1 {
+        return Err(ContractError::LessThanOneEpoch {});
+    }
+
+    let mut drip_pool = DRIP_POOLS.may_load(deps.storage, token.clone())?.ok_or(
+        ContractError::DripPoolNotFound {
+            token: token.clone(),
+        },
+    )?;
+
+    // Only the creator can top up the pool, since it receives the refund if cancelled
+    if drip_pool.creator != sender {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    if drip_pool.emission_schedule != (EmissionSchedule::Constant {}) {
+        return Err(ContractError::UnsupportedTopUp {});
+    }
+
+    let top_up_amount = drip_pool
+        .tokens_per_epoch
+        .checked_mul(Uint128::from(additional_epochs))?;
+    if amount != top_up_amount {
+        return Err(ContractError::WrongTokensAmount {
+            tokens_amount: amount,
+            total_tokens: top_up_amount,
+        });
+    }
+
+    match drip_pool.status {
+        PoolStatus::Pending | PoolStatus::Active | PoolStatus::Paused => {}
+        PoolStatus::Finished | PoolStatus::Drained if !drip_pool.is_expired(&env.block.time) => {
+            drip_pool.status = PoolStatus::Active;
+            DRIP_TOKENS.update(deps.storage, |mut drip_tokens| -> StdResult<_> {
+                drip_tokens.push(token.clone());
+                Ok(drip_tokens)
+            })?;
+        }
+        _ => return Err(ContractError::InactiveDripPool { token }),
+    }
+
+    drip_pool.add_available_tokens(amount);
+    drip_pool.epochs_number += additional_epochs;
+    DRIP_POOLS.save(deps.storage, token.clone(), &drip_pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "top_up_drip_pool")
+        .add_attribute("token", token)
+        .add_attribute("amount", amount)
+        .add_attribute("epochs_number", drip_pool.epochs_number.to_string()))
+}
+
+/// Drip every active pool for one epoch. Returns the tokens of the pools still dripping
+/// and the messages minting the emitted shares as receipt tokens held by the contract
+/// until claimed and refunding to their creator the tokens left in expired pools.
+pub fn update_drip_pools(
+    deps: &mut DepsMut,
+    env: &Env,
+    drip_tokens: Vec<String>,
+    emitted_shares: Uint128,
+) -> Result<(Vec<String>, Vec<CosmosMsg>), ContractError> {
+    let mut tokens_to_retain: Vec<String> = vec![];
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut closed_pools: Vec<(String, Uint128)> = vec![];
+    // Only token in the drip tokens vector are associated to active pools.
+    for drip_token in drip_tokens {
+        DRIP_POOLS.update(
+            deps.storage,
+            drip_token.clone(),
+            |drip_pool| -> Result<_, ContractError> {
+                // Drip pool has been initialized during pool creation so .unwrap() should be ok
+                let mut drip_pool = drip_pool.unwrap();
+
+                let epoch = drip_pool.epoch;
+                if drip_epoch(&mut drip_pool, env, emitted_shares)? {
+                    tokens_to_retain.push(drip_token.clone());
+                } else {
+                    closed_pools.push((drip_token.clone(), drip_pool.shares_per_stake));
+                }
+
+                // Tokens not distributed before the end time go back to the creator
+                let refund_amount = drip_pool.drip_token.get_available_amount();
+                if drip_pool.is_expired(&env.block.time) && !refund_amount.is_zero() {
+                    drip_pool.remove_available_tokens(refund_amount);
+                    msgs.push(drip_pool.send_tokens_message(refund_amount, &drip_pool.creator)?);
+                }
+
+                if let (Some(receipt_token), true, false) = (
+                    &drip_pool.receipt_token,
+                    drip_pool.epoch > epoch,
+                    emitted_shares.is_zero(),
+                ) {
+                    msgs.push(receipt_token_message(
+                        receipt_token,
+                        Cw20ExecuteMsg::Mint {
+                            recipient: env.contract.address.to_string(),
+                            amount: emitted_shares,
+                        },
+                    )?);
+                }
+
+                Ok(drip_pool)
+            },
+        )?;
+    }
+    for (drip_token, index) in closed_pools {
+        close_drip_pool(deps.storage, &drip_token, index)?;
+    }
+    Ok((tokens_to_retain, msgs))
+}
+
+/// Drip a pool for one epoch issuing the emitted shares and releasing the epoch tokens.
+/// Returns whether the pool is still dripping after the distribution.
+fn drip_epoch(
+    drip_pool: &mut DripPool,
+    env: &Env,
+    emitted_shares: Uint128,
+) -> Result<bool, ContractError> {
+    // Paused and pending pools are skipped without consuming an epoch
+    if drip_pool.status == PoolStatus::Paused || drip_pool.is_pending(&env.block.time) {
+        return Ok(true);
+    }
+
+    // Pools past their end time are no more active
+    if drip_pool.is_expired(&env.block.time) {
+        drip_pool.status = PoolStatus::Finished;
+        return Ok(false);
+    }
+
+    if drip_pool.epoch > drip_pool.epochs_number {
+        return Err(ContractError::InvalidActiveDripPool {});
+    }
+
+    let epoch_tokens = drip_pool.epoch_tokens();
+    if drip_pool.drip_token.get_available_amount() < epoch_tokens {
+        return Err(ContractError::DripPoolHasNotENoughFunds);
+    }
+
+    drip_pool.issued_shares += emitted_shares;
+    drip_pool.shares_per_stake += Uint128::one();
+    drip_pool.remove_available_tokens(epoch_tokens);
+    drip_pool.withdrawable_tokens += epoch_tokens;
+    drip_pool.epoch += 1;
+
+    if drip_pool.epoch < drip_pool.epochs_number {
+        drip_pool.status = PoolStatus::Active;
+        Ok(true)
+    } else {
+        drip_pool.status = PoolStatus::Finished;
+        Ok(false)
+    }
+}
+
+/// Tokens deposited along with the creation of a drip pool
+pub enum Deposit {
+    /// Native tokens attached to the message
+    Native(Vec<Coin>),
+    /// Cw20 tokens sent to the contract
+    Cw20 { address: Addr, amount: Uint128 },
+}
+
+impl Deposit {
+    /// Amount deposited of the given denom or cw20 address
+    pub fn amount_of(&self, token: &str) -> Uint128 {
+        match self {
+            Deposit::Native(funds) => funds
+                .iter()
+                .filter(|coin| coin.denom == token)
+                .map(|coin| coin.amount)
+                .sum(),
+            Deposit::Cw20 { address, amount } if address.as_str() == token => *amount,
+            Deposit::Cw20 { .. } => Uint128::zero(),
+        }
+    }
+}
+
+/// Where withdrawn tokens are delivered
+pub enum Destination {
+    /// Transfer tokens to the recipient, the owner of the shares by default. Native
+    /// tokens in the staking denom are restaked to the validator if specified.
+    Transfer {
+        recipient: Option<String>,
+        restake_to: Option<String>,
+    },
+    /// Send tokens to a contract along with a message to execute
+    Call { contract: String, msg: Binary },
+}
+
+/// Burn the owner shares to withdraw the associated tokens. Withdrawal can be limited to
+/// a subset of drip tokens and to an amount of shares to burn from each selected pool.
+/// The sender can withdraw on behalf of the owner only if authorized by it.
+fn execute_withdraw_tokens(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tokens: Option<Vec<String>>,
+    shares: Option<Uint128>,
+    owner: Option<String>,
+    destination: Destination,
+) -> Result<Response, ContractError> {
+    let owner = match owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender.clone(),
+    };
+    let (recipient, call_msg, restake_to) = match destination {
+        Destination::Transfer {
+            recipient,
+            restake_to,
+        } => {
+            let recipient = match recipient {
+                Some(recipient) => deps.api.addr_validate(&recipient)?,
+                None => owner.clone(),
+            };
+            let restake_to = match restake_to {
+                Some(validator) => {
+                    if deps.querier.query_validator(&validator)?.is_none() {
+                        return Err(ContractError::ValidatorNotFound { validator });
+                    }
+                    Some((validator, deps.querier.query_bonded_denom()?))
+                }
+                None => None,
+            };
+            (recipient, None, restake_to)
+        }
+        Destination::Call { contract, msg } => {
+            (deps.api.addr_validate(&contract)?, Some(msg), None)
+        }
+    };
+    if owner != info.sender {
+        assert_authorized(deps.as_ref(), &env, &owner, &info.sender, &recipient)?;
+    }
+
+    let tokens = tokens.map(|mut tokens| {
+        tokens.sort();
+        tokens.dedup();
+        tokens
+    });
+    // Shares of different pools have unrelated values
+    if shares.is_some() && !matches!(&tokens, Some(tokens) if tokens.len() == 1) {
+        return Err(ContractError::SharesOfManyTokens {});
+    }
+
+    sync_participant(&mut deps, &owner)?;
+
+    let participant_shares = match tokens {
+        Some(tokens) => tokens
+            .into_iter()
+            .map(|token| {
+                let held = participants_shares()
+                    .may_load(deps.storage, (token.clone(), &owner))?
+                    .ok_or(ContractError::NoShares {})?;
+                if DRIP_POOLS.load(deps.storage, token.clone())?.status == PoolStatus::Paused {
+                    return Err(ContractError::PausedDripPool { token });
+                }
+                Ok((token, held))
+            })
+            .collect::<Result<Vec<_>, ContractError>>()?,
+        // Paused pools are left untouched
+        None => participants_shares()
+            .idx
+            .owner
+            .prefix(owner.clone())
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| item.map(|((token, _), shares)| (token, shares)))
+            .filter(|item| match item {
+                Ok((token, _)) => !matches!(
+                    DRIP_POOLS.load(deps.storage, token.clone()),
+                    Ok(DripPool {
+                        status: PoolStatus::Paused,
+                        ..
+                    })
+                ),
+                Err(_) => true,
+            })
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    if participant_shares.is_empty() || shares == Some(Uint128::zero()) {
+        return Err(ContractError::NoTokensToWithdraw {});
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "withdraw_tokens")
+        .add_attribute("owner", owner.to_string())
+        .add_attribute("recipient", recipient.to_string());
+    for (token, held_shares) in participant_shares {
+        let burn_shares = shares.unwrap_or(held_shares);
+        if burn_shares > held_shares {
+            return Err(ContractError::NotEnoughShares {
+                token,
+                shares: held_shares,
+            });
+        }
+
+        let mut pool = DRIP_POOLS.load(deps.storage, token.clone())?;
+        // Vesting tokens are locked for the owner, so they cannot be delivered along with
+        // a message, restaked or vest for someone else
+        if pool.vesting.is_some() {
+            if call_msg.is_some() {
+                return Err(ContractError::VestingCall { token });
+            }
+            if restake_to.is_some() {
+                return Err(ContractError::VestingRestake { token });
+            }
+            if recipient != owner {
+                return Err(ContractError::VestingRecipient { token });
+            }
+        }
+        let (withdraw_amount, penalty) = pool.remove_tokens_and_shares(burn_shares);
+        pool.update_drained_status();
+        if let (false, Some(duration)) = (withdraw_amount.is_zero(), pool.vesting) {
+            let position = VestingPosition {
+                token: token.clone(),
+                amount: withdraw_amount,
+                claimed: Uint128::zero(),
+                start_height: env.block.height,
+                start_time: env.block.time,
+                duration,
+            };
+            VESTING_POSITIONS.update(deps.storage, &owner, |positions| -> StdResult<_> {
+                let mut positions = positions.unwrap_or_default();
+                positions.push(position);
+                Ok(positions)
+            })?;
+            res = res.add_attribute("vesting", owner.to_string());
+        } else if !withdraw_amount.is_zero() {
+            let send_msgs = match (&call_msg, &restake_to, &pool.drip_token) {
+                (Some(msg), _, _) => {
+                    vec![pool.call_contract_message(withdraw_amount, &recipient, msg.clone())?]
+                }
+                (None, Some((validator, bonded_denom)), DripToken::Native { denom, .. })
+                    if denom == bonded_denom =>
+                {
+                    restake_messages(
+                        deps.as_ref(),
+                        &env,
+                        &recipient,
+                        validator,
+                        Coin {
+                            denom: denom.clone(),
+                            amount: withdraw_amount,
+                        },
+                    )?
+                }
+                _ => vec![pool.send_tokens_message(withdraw_amount, &recipient)?],
+            };
+            res = res.add_messages(send_msgs);
+        }
+        DRIP_POOLS.save(deps.storage, token.clone(), &pool)?;
+
+        // Receipt tokens backing the burned shares are held by the contract
+        if let Some(receipt_token) = &pool.receipt_token {
+            res = res.add_message(receipt_token_message(
+                receipt_token,
+                Cw20ExecuteMsg::Burn {
+                    amount: burn_shares,
+                },
+            )?);
+        }
+
+        if burn_shares == held_shares {
+            participants_shares().remove(deps.storage, (token.clone(), &owner))?;
+        } else {
+            participants_shares().save(
+                deps.storage,
+                (token.clone(), &owner),
+                &(held_shares - burn_shares),
+            )?;
+        }
+
+        res = res
+            .add_attribute("token", token)
+            .add_attribute("shares", burn_shares)
+            .add_attribute("penalty", penalty)
+            .add_attribute("amount", withdraw_amount);
+    }
+
+    Ok(res)
+}
+
+/// Settle the sender shares and transfer the ones of drip pools with a receipt token,
+/// held by the contract, to the sender.
+fn execute_claim_receipt_tokens(
+    mut deps: DepsMut,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    sync_participant(&mut deps, &info.sender)?;
+
+    let participant_shares = participants_shares()
+        .idx
+        .owner
+        .prefix(info.sender.clone())
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|((token, _), shares)| (token, shares)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "claim_receipt_tokens")
+        .add_attribute("address", info.sender.to_string());
+    for (token, shares) in participant_shares {
+        let receipt_token = match DRIP_POOLS.load(deps.storage, token.clone())?.receipt_token {
+            Some(receipt_token) => receipt_token,
+            None => continue,
+        };
+        participants_shares().remove(deps.storage, (token.clone(), &info.sender))?;
+        res = res
+            .add_message(receipt_token_message(
+                &receipt_token,
+                Cw20ExecuteMsg::Transfer {
+                    recipient: info.sender.to_string(),
+                    amount: shares,
+                },
+            )?)
+            .add_attribute("token", token)
+            .add_attribute("shares", shares);
+    }
+
+    if res.messages.is_empty() {
+        return Err(ContractError::NoShares {});
+    }
+    Ok(res)
+}
+
+/// Message executed by the contract on the receipt token of a drip pool
+fn receipt_token_message(receipt_token: &Addr, msg: Cw20ExecuteMsg) -> StdResult<CosmosMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: receipt_token.to_string(),
+        msg: to_binary(&msg)?,
+        funds: vec![],
+    }
+    .into())
+}
+
+/// Send to the sender the unlocked tokens of all its vesting positions. Fully claimed
+/// positions are removed.
+fn execute_claim_vested(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let positions = VESTING_POSITIONS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+
+    let mut claims: Vec<(String, Uint128)> = vec![];
+    let mut remaining_positions = vec![];
+    for mut position in positions {
+        let claimable = position.claimable(&env.block);
+        if !claimable.is_zero() {
+            position.claimed += claimable;
+            match claims
+                .iter_mut()
+                .find(|(token, _)| *token == position.token)
+            {
+                Some((_, amount)) => *amount += claimable,
+                None => claims.push((position.token.clone(), claimable)),
+            }
+        }
+        if position.claimed < position.amount {
+            remaining_positions.push(position);
+        }
+    }
+
+    if claims.is_empty() {
+        return Err(ContractError::NoTokensToWithdraw {});
+    }
+
+    if remaining_positions.is_empty() {
+        VESTING_POSITIONS.remove(deps.storage, &info.sender);
+    } else {
+        VESTING_POSITIONS.save(deps.storage, &info.sender, &remaining_positions)?;
+    }
+
+    let mut res = Response::new()
+        .add_attribute("action", "claim_vested")
+        .add_attribute("address", info.sender.to_string());
+    for (token, amount) in claims {
+        let pool = DRIP_POOLS.load(deps.storage, token.clone())?;
+        res = res
+            .add_message(pool.send_tokens_message(amount, &info.sender)?)
+            .add_attribute("token", token)
+            .add_attribute("amount", amount);
+    }
+    Ok(res)
+}
+
+/// Messages delegating native tokens on behalf of the recipient. Tokens are bonded through
+/// the liquid staking contract if configured, otherwise they are sent to the recipient and
+/// delegated with an authz MsgExec.
+fn restake_messages(
+    deps: Deps,
+    env: &Env,
+    recipient: &Addr,
+    validator: &str,
+    amount: Coin,
+) -> StdResult<Vec<CosmosMsg>> {
+    let config = CONFIG.load(deps.storage)?;
+    Ok(match config.liquid_staking {
+        Some(liquid_staking) => vec![WasmMsg::Execute {
+            contract_addr: liquid_staking.to_string(),
+            msg: to_binary(&LiquidStakingExecuteMsg::Bond {
+                receiver: Some(recipient.to_string()),
+            })?,
+            funds: vec![amount],
+        }
+        .into()],
+        None => vec![
+            BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![amount.clone()],
+            }
+            .into(),
+            authz_delegate_msg(&env.contract.address, recipient, validator, &amount),
+        ],
+    })
+}
+
+/// Allow an operator to withdraw tokens on behalf of the sender until the expiry. The
+/// tokens can be sent only to the sender or to the allowed recipient.
+fn execute_authorize(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    operator: String,
+    expiry: Option<Expiration>,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let operator = deps.api.addr_validate(&operator)?;
+    if operator == info.sender {
+        return Err(ContractError::SelfAuthorization {});
+    }
+
+    let expiry = expiry.unwrap_or_default();
+    if expiry.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+
+    let recipient = recipient
+        .map(|recipient| deps.api.addr_validate(&recipient))
+        .transpose()?;
+
+    AUTHORIZATIONS.save(
+        deps.storage,
+        (&info.sender, &operator),
+        &Authorization {
+            expiry,
+            recipient: recipient.clone(),
+        },
+    )?;
+
+    let mut res = Response::new()
+        .add_attribute("action", "authorize")
+        .add_attribute("owner", info.sender)
+        .add_attribute("operator", operator)
+        .add_attribute("expiry", expiry.to_string());
+    if let Some(recipient) = recipient {
+        res = res.add_attribute("recipient", recipient);
+    }
+    Ok(res)
+}
+
+/// Remove the authorization given by the sender to an operator
+fn execute_revoke(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+) -> Result<Response, ContractError> {
+    let operator = deps.api.addr_validate(&operator)?;
+    AUTHORIZATIONS.remove(deps.storage, (&info.sender, &operator));
+
+    let res = Response::new()
+        .add_attribute("action", "revoke")
+        .add_attribute("owner", info.sender)
+        .add_attribute("operator", operator);
+    Ok(res)
+}
+
+/// Move shares of a drip pool from the sender to the recipient
+fn execute_send_shares(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+    token: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    transfer_shares(&mut deps, &info.sender, &recipient, &token, amount)?;
+
+    let res = Response::new()
+        .add_attribute("action", "send_shares")
+        .add_attribute("owner", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("token", token)
+        .add_attribute("shares", amount);
+    Ok(res)
+}
+
+/// Allow a spender to transfer more shares of a drip pool on behalf of the sender
+fn execute_increase_share_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token: String,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    if spender == info.sender {
+        return Err(ContractError::SelfAuthorization {});
+    }
+
+    let allowance = SHARE_ALLOWANCES.update(
+        deps.storage,
+        (&info.sender, &spender, token.clone()),
+        |allowance| -> Result<_, ContractError> {
+            let mut allowance = allowance.unwrap_or(ShareAllowance {
+                amount: Uint128::zero(),
+                expires: Expiration::Never {},
+            });
+            if let Some(expires) = expires {
+                if expires.is_expired(&env.block) {
+                    return Err(ContractError::Expired {});
+                }
+                allowance.expires = expires;
+            }
+            allowance.amount += amount;
+            Ok(allowance)
+        },
+    )?;
+
+    let res = Response::new()
+        .add_attribute("action", "increase_share_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("token", token)
+        .add_attribute("allowance", allowance.amount);
+    Ok(res)
+}
+
+/// Reduce the shares of a drip pool a spender can transfer on behalf of the sender
+fn execute_decrease_share_allowance(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    spender: String,
+    token: String,
+    amount: Uint128,
+    expires: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    if spender == info.sender {
+        return Err(ContractError::SelfAuthorization {});
+    }
+
+    let key = (&info.sender, &spender, token.clone());
+    let mut allowance = SHARE_ALLOWANCES
+        .may_load(deps.storage, key.clone())?
+        .unwrap_or(ShareAllowance {
+            amount: Uint128::zero(),
+            expires: Expiration::Never {},
+        });
+    allowance.amount = allowance.amount.saturating_sub(amount);
+    if allowance.amount.is_zero() {
+        SHARE_ALLOWANCES.remove(deps.storage, key);
+    } else {
+        if let Some(expires) = expires {
+            if expires.is_expired(&env.block) {
+                return Err(ContractError::Expired {});
+            }
+            allowance.expires = expires;
+        }
+        SHARE_ALLOWANCES.save(deps.storage, key, &allowance)?;
+    }
+
+    let res = Response::new()
+        .add_attribute("action", "decrease_share_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("token", token)
+        .add_attribute("allowance", allowance.amount);
+    Ok(res)
+}
+
+/// Transfer shares of a drip pool on behalf of the owner deducting them from the
+/// allowance given to the sender
+fn execute_transfer_shares_from(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+    recipient: String,
+    token: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let key = (&owner, &info.sender, token.clone());
+    let mut allowance = SHARE_ALLOWANCES
+        .may_load(deps.storage, key.clone())?
+        .ok_or(ContractError::Unauthorized {})?;
+    if allowance.expires.is_expired(&env.block) {
+        return Err(ContractError::Expired {});
+    }
+    if amount > allowance.amount {
+        return Err(ContractError::ShareAllowanceExceeded {
+            allowance: allowance.amount,
+        });
+    }
+    allowance.amount -= amount;
+    if allowance.amount.is_zero() {
+        SHARE_ALLOWANCES.remove(deps.storage, key);
+    } else {
+        SHARE_ALLOWANCES.save(deps.storage, key, &allowance)?;
+    }
+
+    transfer_shares(&mut deps, &owner, &recipient, &token, amount)?;
+
+    let res = Response::new()
+        .add_attribute("action", "transfer_shares_from")
+        .add_attribute("owner", owner)
+        .add_attribute("spender", info.sender)
+        .add_attribute("recipient", recipient)
+        .add_attribute("token", token)
+        .add_attribute("shares", amount);
+    Ok(res)
+}
+
+/// Settle the owner shares and move an amount of them to the recipient
+fn transfer_shares(
+    deps: &mut DepsMut,
+    owner: &Addr,
+    recipient: &Addr,
+    token: &str,
+    amount: Uint128,
+) -> Result<(), ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::ZeroShares {});
+    }
+
+    sync_participant(deps, owner)?;
+    let held_shares = participants_shares()
+        .may_load(deps.storage, (token.to_string(), owner))?
+        .ok_or(ContractError::NoShares {})?;
+    if amount > held_shares {
+        return Err(ContractError::NotEnoughShares {
+            token: token.to_string(),
+            shares: held_shares,
+        });
+    }
+
+    if amount == held_shares {
+        participants_shares().remove(deps.storage, (token.to_string(), owner))?;
+    } else {
+        participants_shares().save(
+            deps.storage,
+            (token.to_string(), owner),
+            &(held_shares - amount),
+        )?;
+    }
+    participants_shares().update(
+        deps.storage,
+        (token.to_string(), recipient),
+        |shares| -> StdResult<_> { Ok(shares.unwrap_or_default() + amount) },
+    )?;
+    Ok(())
+}
+
+/// Request to count the weight of an address toward the sender. The link is effective once
+/// confirmed by the linked address.
+fn execute_link_address(
+    deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    if address == info.sender {
+        return Err(ContractError::SelfLink {});
+    }
+    assert_linkable(deps.as_ref(), &info.sender, &address)?;
+
+    PENDING_LINKS.save(deps.storage, &address, &info.sender)?;
+
+    let res = Response::new()
+        .add_attribute("action", "link_address")
+        .add_attribute("primary", info.sender)
+        .add_attribute("address", address);
+    Ok(res)
+}
+
+/// Confirm the link of the sender to the primary participant, whose stake is refreshed
+fn execute_confirm_link(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    primary: String,
+) -> Result<Response, ContractError> {
+    let primary = deps.api.addr_validate(&primary)?;
+    match PENDING_LINKS.may_load(deps.storage, &info.sender)? {
+        Some(pending) if pending == primary => {}
+        _ => return Err(ContractError::NoPendingLink {}),
+    }
+    assert_linkable(deps.as_ref(), &primary, &info.sender)?;
+
+    PENDING_LINKS.remove(deps.storage, &info.sender);
+    LINKED_ADDRESSES.save(deps.storage, &info.sender, &primary)?;
+    PRIMARY_LINKS.save(deps.storage, (&primary, &info.sender), &Empty {})?;
+    let total_staked = sync_participant(&mut deps, &primary)?;
+
+    let res = Response::new()
+        .add_attribute("action", "confirm_link")
+        .add_attribute("primary", primary)
+        .add_attribute("address", info.sender)
+        .add_attribute("stake", total_staked);
+    Ok(res)
+}
+
+/// Remove the link between a primary participant and a linked address. Both can unlink.
+fn execute_unlink_address(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    let address = deps.api.addr_validate(&address)?;
+    // The sender is either the primary unlinking an address or the linked address
+    // unlinking from its primary
+    let (primary, linked) = match LINKED_ADDRESSES.may_load(deps.storage, &address)? {
+        Some(primary) if primary == info.sender => (primary, address),
+        _ => match LINKED_ADDRESSES.may_load(deps.storage, &info.sender)? {
+            Some(primary) if primary == address => (primary, info.sender),
+            _ => return Err(ContractError::NotLinked {}),
+        },
+    };
+
+    // Shares accrued so far are settled with the linked weight
+    settle_participant(&mut deps, &primary)?;
+    LINKED_ADDRESSES.remove(deps.storage, &linked);
+    PRIMARY_LINKS.remove(deps.storage, (&primary, &linked));
+    let total_staked = sync_participant(&mut deps, &primary)?;
+
+    let res = Response::new()
+        .add_attribute("action", "unlink_address")
+        .add_attribute("primary", primary)
+        .add_attribute("address", linked)
+        .add_attribute("stake", total_staked);
+    Ok(res)
+}
+
+/// Raise an error if the address cannot be linked to the primary participant. An address
+/// can be linked only once and cannot be itself a participant or have linked addresses.
+fn assert_linkable(deps: Deps, primary: &Addr, address: &Addr) -> Result<(), ContractError> {
+    if LINKED_ADDRESSES.has(deps.storage, address) {
+        return Err(ContractError::AlreadyLinked {
+            address: address.to_string(),
+        });
+    }
+    if LINKED_ADDRESSES.has(deps.storage, primary) {
+        return Err(ContractError::AlreadyLinked {
+            address: primary.to_string(),
+        });
+    }
+    if PARTICIPANTS.has(deps.storage, address)
+        || PRIMARY_LINKS
+            .prefix(address)
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some()
+    {
+        return Err(ContractError::LinkToParticipant {
+            address: address.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Raise an error if the operator has no valid authorization from the owner or cannot
+/// send the tokens to the recipient
+pub fn assert_authorized(
+    deps: Deps,
+    env: &Env,
+    owner: &Addr,
+    operator: &Addr,
+    recipient: &Addr,
+) -> Result<(), ContractError> {
+    let authorization = match AUTHORIZATIONS.may_load(deps.storage, (owner, operator))? {
+        Some(authorization) if !authorization.expiry.is_expired(&env.block) => authorization,
+        _ => return Err(ContractError::Unauthorized {}),
+    };
+    if recipient != owner && authorization.recipient.as_ref() != Some(recipient) {
+        return Err(ContractError::UnauthorizedRecipient {
+            recipient: recipient.to_string(),
+        });
+    }
+    Ok(())
+}
+
+//==================================================================================================
+// QUERY
+//==================================================================================================
+
+pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
+    Ok(ConfigResponse {
+        config: CONFIG.load(deps.storage)?,
+    })
+}
+
+fn query_drip_tokens(deps: Deps) -> StdResult<DripTokensResponse> {
+    Ok(DripTokensResponse {
+        drip_tokens: DRIP_TOKENS.load(deps.storage)?,
+    })
+}
+
+fn query_links(deps: Deps, address: String) -> StdResult<LinksResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let linked = PRIMARY_LINKS
+        .prefix(&address)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(LinksResponse {
+        primary: LINKED_ADDRESSES.may_load(deps.storage, &address)?,
+        linked,
+        pending: PENDING_LINKS.may_load(deps.storage, &address)?,
+    })
+}
+
+fn query_lst_sources(deps: Deps) -> StdResult<LstSourcesResponse> {
+    let lst_sources = LST_SOURCES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, lst_source)| lst_source))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(LstSourcesResponse { lst_sources })
+}
+
+fn query_allowed_drip_tokens(deps: Deps) -> StdResult<AllowedDripTokensResponse> {
+    let allowed_drip_tokens = ALLOWED_DRIP_TOKENS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (token, min_initial_amount) = item?;
+            Ok(AllowedDripToken {
+                token,
+                min_initial_amount,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(AllowedDripTokensResponse {
+        allowed_drip_tokens,
+    })
+}
+
+fn query_participants(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ParticipantsResponse> {
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let participants = PARTICIPANTS
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .collect::<StdResult<Vec<Addr>>>()?;
+    Ok(ParticipantsResponse { participants })
+}
+
+/// Returns a drip pool, reporting as active a pending pool whose start time has passed
+fn query_drip_pool(deps: Deps, env: Env, token: String) -> StdResult<DripPoolResponse> {
+    let drip_pool = DRIP_POOLS.may_load(deps.storage, token)?;
+    match drip_pool {
+        Some(mut drip_pool) => {
+            drip_pool.refresh_status(&env.block.time);
+            Ok(DripPoolResponse {
+                drip_pool: Some(drip_pool),
+            })
+        }
+        None => Ok(DripPoolResponse { drip_pool: None }),
+    }
+}
+
+/// Returns a page of drip pools, optionally filtered by their current status. Pools with
+/// another status are skipped, so the cost of a filtered page grows with the number of drip pools.
+fn query_drip_pools(
+    deps: Deps,
+    env: Env,
+    status: Option<PoolStatus>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<DripPoolsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let drip_pools = DRIP_POOLS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| {
+            item.map(|(_, mut drip_pool)| {
+                drip_pool.refresh_status(&env.block.time);
+                drip_pool
+            })
+        })
+        .filter(|item| match (item, &status) {
+            (Ok(drip_pool), Some(status)) => drip_pool.status == *status,
+            _ => true,
+        })
+        .take(limit)
+        .collect::<StdResult<Vec<DripPool>>>()?;
+    Ok(DripPoolsResponse { drip_pools })
+}
+
+/// Returns a page of the settled and pending shares of an address. Pools without shares
+/// are skipped, so the cost of a page grows with the number of drip pools.
+fn query_participant_shares(
+    deps: Deps,
+    address: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<ParticipantSharesResponse> {
+    let address = &deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let shares = participant_shares(deps, address, start_after)?
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
     Ok(ParticipantSharesResponse { shares })
 }
+
+/// Returns a page of the addresses holding shares of a drip pool with their settled and
+/// pending shares and the tokens they would withdraw. The settled shareholders are merged
+/// with the participants, both ordered by address, so that participants holding only
+/// pending shares are listed. Participants without shares of the pool are skipped.
+fn query_pool_shareholders(
+    deps: Deps,
+    token: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<PoolShareholdersResponse> {
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let drip_pool = DRIP_POOLS.load(deps.storage, token.clone())?;
+
+    let mut settled = participants_shares()
+        .prefix(token.clone())
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .peekable();
+    let mut participants = PARTICIPANTS
+        .keys(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| item.map(|address| (address, Uint128::zero())))
+        .peekable();
+    let holders = std::iter::from_fn(|| {
+        let next_settled = match settled.peek() {
+            Some(Ok((address, _))) => Some(address.clone()),
+            Some(Err(_)) => return settled.next(),
+            None => None,
+        };
+        let next_participant = match participants.peek() {
+            Some(Ok((address, _))) => Some(address.clone()),
+            Some(Err(_)) => return participants.next(),
+            None => None,
+        };
+        match (next_settled, next_participant) {
+            (Some(settled_address), Some(participant)) if participant < settled_address => {
+                participants.next()
+            }
+            (Some(settled_address), Some(participant)) if participant == settled_address => {
+                participants.next();
+                settled.next()
+            }
+            (Some(_), _) => settled.next(),
+            (None, _) => participants.next(),
+        }
+    });
+
+    let shareholders = holders
+        .map(|item| {
+            let (address, settled) = item?;
+            let stake = PARTICIPANTS
+                .may_load(deps.storage, &address)?
+                .unwrap_or_default();
+            let shares = settled + accrued_shares(deps, &address, stake, &token, &drip_pool)?;
+            let tokens = drip_pool.tokens_from_shares(shares);
+            Ok(Shareholder {
+                address,
+                shares,
+                amount: tokens - drip_pool.early_exit_penalty(tokens),
+            })
+        })
+        .filter(|item| !matches!(item, Ok(shareholder) if shareholder.shares.is_zero()))
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PoolShareholdersResponse { shareholders })
+}
+
+/// Preview the next distribution applying to a copy of the dripping pools the same logic
+/// of `DistributeShares`. The current weight of a page of participants is compared with
+/// the stake accounted for the distribution.
+fn query_simulate_distribution(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<SimulateDistributionResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    // Distributions refresh the stake of every participant when counting liquid staking
+    // tokens
+    let emitted_shares = if lst_stake_counted(deps, &config) {
+        PARTICIPANTS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|address| query_eligible_stake(deps, &config, &address?))
+            .sum::<StdResult<Uint128>>()?
+    } else {
+        TOTAL_STAKE.load(deps.storage)?
+    };
+
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let participants = PARTICIPANTS
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (address, stake) = item?;
+            let weight = query_eligible_stake(deps, &config, &address)?;
+            Ok(SimulatedParticipant {
+                address,
+                stake,
+                weight,
+                stale: weight != stake,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let drip_pools = DRIP_TOKENS
+        .load(deps.storage)?
+        .into_iter()
+        .map(|token| {
+            let mut drip_pool = DRIP_POOLS.load(deps.storage, token.clone())?;
+            let (issued_shares, withdrawable_tokens) =
+                (drip_pool.issued_shares, drip_pool.withdrawable_tokens);
+            drip_epoch(&mut drip_pool, &env, emitted_shares)
+                .map_err(|err| StdError::generic_err(err.to_string()))?;
+            Ok(SimulatedDripPool {
+                token,
+                shares: drip_pool.issued_shares - issued_shares,
+                tokens: drip_pool.withdrawable_tokens - withdrawable_tokens,
+                status: drip_pool.status,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(SimulateDistributionResponse {
+        ready: config.next_distribution_time <= env.block.time.seconds(),
+        next_distribution_time: config.next_distribution_time,
+        emitted_shares,
+        participants,
+        drip_pools,
+    })
+}
+
+/// Returns the tokens an address would receive burning all its shares of every drip pool,
+/// computed like the withdrawal
+fn query_claimable(deps: Deps, address: String) -> StdResult<ClaimableResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let claimable = participant_shares(deps, &address, None)?
+        .map(|item| {
+            let (token, shares) = item?;
+            let drip_pool = DRIP_POOLS.load(deps.storage, token.clone())?;
+            let tokens = drip_pool.tokens_from_shares(shares);
+            let penalty = drip_pool.early_exit_penalty(tokens);
+            Ok(ClaimableTokens {
+                token,
+                denom: drip_pool.drip_token.get_denom(),
+                shares,
+                amount: tokens - penalty,
+                penalty,
+                status: drip_pool.status,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ClaimableResponse { claimable })
+}
+
+fn query_authorization(
+    deps: Deps,
+    owner: String,
+    operator: String,
+) -> StdResult<AuthorizationResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let operator = deps.api.addr_validate(&operator)?;
+    let authorization = AUTHORIZATIONS.may_load(deps.storage, (&owner, &operator))?;
+    Ok(AuthorizationResponse {
+        expiry: authorization
+            .as_ref()
+            .map(|authorization| authorization.expiry),
+        recipient: authorization.and_then(|authorization| authorization.recipient),
+    })
+}
+
+/// Returns the shares of a drip pool a spender can transfer for an owner. Expired
+/// allowances are reported as zero.
+fn query_share_allowance(
+    deps: Deps,
+    env: Env,
+    owner: String,
+    spender: String,
+    token: String,
+) -> StdResult<ShareAllowanceResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let spender = deps.api.addr_validate(&spender)?;
+    let allowance = SHARE_ALLOWANCES
+        .may_load(deps.storage, (&owner, &spender, token))?
+        .unwrap_or(ShareAllowance {
+            amount: Uint128::zero(),
+            expires: Expiration::Never {},
+        });
+    let amount = if allowance.expires.is_expired(&env.block) {
+        Uint128::zero()
+    } else {
+        allowance.amount
+    };
+    Ok(ShareAllowanceResponse {
+        allowance: amount,
+        expires: allowance.expires,
+    })
+}
+
+/// Returns the vesting positions of an address and the tokens claimable right now
+fn query_vesting_positions(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<VestingPositionsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let positions = VESTING_POSITIONS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+
+    let mut claimable: Vec<(String, Uint128)> = vec![];
+    for position in positions.iter() {
+        let amount = position.claimable(&env.block);
+        match claimable
+            .iter_mut()
+            .find(|(token, _)| *token == position.token)
+        {
+            Some((_, total)) => *total += amount,
+            None => claimable.push((position.token.clone(), amount)),
+        }
+    }
+    Ok(VestingPositionsResponse {
+        positions,
+        claimable,
+    })
+}
@@ -1,4 +1,5 @@
-use cosmwasm_std::{StdError, Uint128};
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -7,6 +8,15 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
     #[error("Unauthorized")]
     Unauthorized {},
     // Add any other custom errors you like here.
@@ -20,6 +30,21 @@ pub enum ContractError {
     #[error("smart contract has not enough tokens, missing: [{token}] [{amount}]")]
     NoFundedContract { token: String, amount: Uint128 },
 
+    #[error("deposit of [{received}] does not coincide with the required [{expected}]")]
+    WrongDeposit {
+        expected: Uint128,
+        received: Uint128,
+    },
+
+    #[error("sponsored drip pools require an initial amount of at least [{min_initial_amount}]")]
+    DripPoolTooSmall { min_initial_amount: Uint128 },
+
+    #[error("token [{token}] is not allowed for sponsored drip pools")]
+    DripTokenNotAllowed { token: String },
+
+    #[error("invalid denom [{denom}]: {reason}")]
+    InvalidDenom { denom: String, reason: String },
+
     #[error("zero token pool is not allowed")]
     ZeroTokenPool {},
 
@@ -29,7 +54,7 @@ pub enum ContractError {
     #[error("no active drip pool")]
     ZeroActiveDripPool {},
 
-    #[error("initial tokens amount [{tokens_amount}] does not coincide with epochs_number X tokens_per_epoch: [{total_tokens}]")]
+    #[error("initial tokens amount [{tokens_amount}] does not coincide with the tokens released by the emission schedule: [{total_tokens}]")]
     WrongTokensAmount {
         tokens_amount: Uint128,
         total_tokens: Uint128,
@@ -41,18 +66,99 @@ pub enum ContractError {
     #[error("no shares for this address")]
     NoShares {},
 
+    #[error("not enough shares of drip pool [{token}], available: [{shares}]")]
+    NotEnoughShares { token: String, shares: Uint128 },
+
+    #[error("shares can be burned from a single drip token at a time")]
+    SharesOfManyTokens {},
+
     #[error("the minimum number of epochs is 1")]
     LessThanOneEpoch {},
 
+    #[error("vesting duration cannot be zero")]
+    ZeroVestingDuration {},
+
+    #[error("tokens of the vesting drip pool [{token}] cannot be sent to a contract")]
+    VestingCall { token: String },
+
+    #[error("tokens of the vesting drip pool [{token}] cannot be restaked")]
+    VestingRestake { token: String },
+
+    #[error("tokens of the vesting drip pool [{token}] can only vest for the owner")]
+    VestingRecipient { token: String },
+
+    #[error("early exit penalty must be lower than 1")]
+    InvalidEarlyExitPenalty {},
+
+    #[error("invalid emission schedule")]
+    InvalidEmissionSchedule {},
+
+    #[error("end time must follow the start time and the current time")]
+    InvalidEndTime {},
+
+    #[error("only drip pools with a constant emission schedule can be topped up")]
+    UnsupportedTopUp {},
+
+    #[error("drip pool for token [{token}] is no more dripping")]
+    InactiveDripPool { token: String },
+
+    #[error("drip pool for token [{token}] is paused")]
+    PausedDripPool { token: String },
+
+    #[error("drip pool for token [{token}] is not paused")]
+    NotPausedDripPool { token: String },
+
     #[error("drip pool should be unactive")]
     InvalidActiveDripPool,
 
     #[error("drip pool has not enough funds to distribute")]
     DripPoolHasNotENoughFunds,
 
+    #[error("[{address}] is not the receipt token of a drip pool")]
+    NotReceiptToken { address: String },
+
+    #[error("unknown reply id [{id}]")]
+    UnknownReplyId { id: u64 },
+
     #[error("no tokens to withdraw")]
     NoTokensToWithdraw {},
 
+    #[error("amount of shares cannot be zero")]
+    ZeroShares {},
+
+    #[error("share allowance exceeded, available: [{allowance}]")]
+    ShareAllowanceExceeded { allowance: Uint128 },
+
+    #[error("cannot authorize yourself")]
+    SelfAuthorization {},
+
+    #[error("operator not allowed to send tokens to [{recipient}]")]
+    UnauthorizedRecipient { recipient: String },
+
+    #[error("expiration already passed")]
+    Expired {},
+
+    #[error("cannot link yourself")]
+    SelfLink {},
+
+    #[error("address [{address}] is already linked")]
+    AlreadyLinked { address: String },
+
+    #[error("address [{address}] is a participant and cannot be linked")]
+    LinkToParticipant { address: String },
+
+    #[error("no pending link from this primary participant")]
+    NoPendingLink {},
+
+    #[error("addresses are not linked")]
+    NotLinked {},
+
+    #[error("validator [{validator}] not found")]
+    ValidatorNotFound { validator: String },
+
+    #[error("participants are not read from a cw4 group")]
+    NoCw4Group {},
+
     #[error("the minimum amount of staking is not met: [{min_staked}]")]
     MinimumDelegationNotSatisfied { min_staked: Uint128 },
 }
@@ -1,9 +1,15 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{to_binary, Addr, CosmosMsg, StdResult, WasmMsg};
+use cosmwasm_std::{
+    from_binary, to_binary, to_vec, Addr, Api, Binary, Coin, ContractResult, CosmosMsg, Deps,
+    Empty, QueryRequest, StdResult, SystemResult, WasmMsg,
+};
+use cw20::{Cw20QueryMsg, TokenInfoResponse};
 
 use crate::msg::ExecuteMsg;
+use crate::state::{DripToken, TokenMetadata};
+use crate::ContractError;
 
 /// CwTemplateContract is a wrapper around Addr that provides a lot of helpers
 /// for working with this.
@@ -25,3 +31,170 @@ impl CwTemplateContract {
         .into())
     }
 }
+
+/// Builds an authz MsgExec that delegates tokens from the delegator to the validator.
+/// The delegator must have granted the grantee the authorization to delegate.
+pub fn authz_delegate_msg(
+    grantee: &Addr,
+    delegator: &Addr,
+    validator: &str,
+    amount: &Coin,
+) -> CosmosMsg {
+    let mut coin = vec![];
+    encode_bytes(&mut coin, 1, amount.denom.as_bytes());
+    encode_bytes(&mut coin, 2, amount.amount.to_string().as_bytes());
+
+    let mut delegate = vec![];
+    encode_bytes(&mut delegate, 1, delegator.as_bytes());
+    encode_bytes(&mut delegate, 2, validator.as_bytes());
+    encode_bytes(&mut delegate, 3, &coin);
+
+    let mut any = vec![];
+    encode_bytes(&mut any, 1, b"/cosmos.staking.v1beta1.MsgDelegate");
+    encode_bytes(&mut any, 2, &delegate);
+
+    let mut exec = vec![];
+    encode_bytes(&mut exec, 1, grantee.as_bytes());
+    encode_bytes(&mut exec, 2, &any);
+
+    CosmosMsg::Stargate {
+        type_url: "/cosmos.authz.v1beta1.MsgExec".to_string(),
+        value: Binary::from(exec),
+    }
+}
+
+/// Check the format of a native denom. Tokenfactory denoms must be
+/// `factory/{creator}/{subdenom}` with a valid creator address and IBC denoms
+/// `ibc/{hash}` with an uppercase hex SHA256 hash. Other denoms must follow the
+/// bank module rules.
+pub fn validate_denom(api: &dyn Api, denom: &str) -> Result<(), ContractError> {
+    let invalid = |reason: &str| ContractError::InvalidDenom {
+        denom: denom.to_string(),
+        reason: reason.to_string(),
+    };
+
+    if let Some(rest) = denom.strip_prefix("factory/") {
+        let (creator, subdenom) = rest
+            .split_once('/')
+            .ok_or_else(|| invalid("missing subdenom"))?;
+        api.addr_validate(creator)
+            .map_err(|_| invalid("invalid creator address"))?;
+        if subdenom.is_empty() {
+            return Err(invalid("empty subdenom"));
+        }
+    } else if let Some(hash) = denom.strip_prefix("ibc/") {
+        if hash.len() != 64
+            || !hash
+                .chars()
+                .all(|c| c.is_ascii_digit() || ('A'..='F').contains(&c))
+        {
+            return Err(invalid("hash must be 64 uppercase hex characters"));
+        }
+    }
+
+    // Bank module rule: [a-zA-Z][a-zA-Z0-9/:._-]{2,127}
+    if !(3..=128).contains(&denom.len()) {
+        return Err(invalid("length must be between 3 and 128"));
+    }
+    if !denom.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(invalid("must start with a letter"));
+    }
+    if !denom
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
+    {
+        return Err(invalid("contains invalid characters"));
+    }
+    Ok(())
+}
+
+/// Bank denom metadata as returned in JSON by the Stargate query
+#[derive(Deserialize)]
+struct DenomMetadataResponse {
+    metadata: DenomMetadata,
+}
+
+#[derive(Deserialize)]
+struct DenomMetadata {
+    #[serde(default)]
+    denom_units: Vec<DenomUnit>,
+    #[serde(default)]
+    display: String,
+}
+
+#[derive(Deserialize)]
+struct DenomUnit {
+    denom: String,
+    #[serde(default)]
+    exponent: u32,
+}
+
+/// Look up the display name and decimals of a drip token. Cw20 tokens use their token
+/// info while native tokens use the bank denom metadata, only if the chain allows the
+/// query. Returns `None` when the metadata are not available, or when the display unit
+/// is not among the denom units, and an error when the chain answers in an unexpected
+/// format.
+pub fn query_token_metadata(
+    deps: Deps,
+    drip_token: &DripToken,
+    denom_metadata: bool,
+) -> StdResult<Option<TokenMetadata>> {
+    match drip_token {
+        DripToken::CW20 { address, .. } => {
+            let token_info: TokenInfoResponse = match deps
+                .querier
+                .query_wasm_smart(address, &Cw20QueryMsg::TokenInfo {})
+            {
+                Ok(token_info) => token_info,
+                Err(_) => return Ok(None),
+            };
+            Ok(Some(TokenMetadata {
+                display: token_info.symbol,
+                decimals: token_info.decimals as u32,
+            }))
+        }
+        DripToken::Native { .. } if !denom_metadata => Ok(None),
+        DripToken::Native { denom, .. } => {
+            let mut data = vec![];
+            encode_bytes(&mut data, 1, denom.as_bytes());
+            let request = QueryRequest::<Empty>::Stargate {
+                path: "/cosmos.bank.v1beta1.Query/DenomMetadata".to_string(),
+                data: Binary::from(data),
+            };
+            // The query is rejected when not allowed by the chain or when the denom has
+            // no metadata
+            let resp = match deps.querier.raw_query(&to_vec(&request)?) {
+                SystemResult::Ok(ContractResult::Ok(resp)) => resp,
+                _ => return Ok(None),
+            };
+            let metadata = from_binary::<DenomMetadataResponse>(&resp)?.metadata;
+            let decimals = match metadata
+                .denom_units
+                .iter()
+                .find(|unit| unit.denom == metadata.display)
+            {
+                Some(unit) => unit.exponent,
+                None => return Ok(None),
+            };
+            Ok(Some(TokenMetadata {
+                display: metadata.display,
+                decimals,
+            }))
+        }
+    }
+}
+
+/// Appends a length-delimited protobuf field to the buffer
+fn encode_bytes(buf: &mut Vec<u8>, field_number: u64, bytes: &[u8]) {
+    encode_varint(buf, field_number << 3 | 2);
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
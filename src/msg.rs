@@ -1,9 +1,14 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Deps, Env, Uint128};
-use cw20::Cw20QueryMsg;
+use cosmwasm_std::{Addr, Api, Binary, Decimal, Deps, Env, StdResult, Timestamp, Uint128};
+use cw20::{Cw20Coin, Cw20QueryMsg, Cw20ReceiveMsg, Denom, MinterResponse};
+use cw_utils::{Duration, Expiration};
 
 use crate::{
-    state::{Config, DripPool, DripToken},
+    helpers::validate_denom,
+    state::{
+        Config, DripPool, DripToken, EmissionSchedule, LstSource, LstToken, PoolStatus,
+        RedemptionRate, SponsorshipConfig, VestingPosition, WeightSource, ALLOWED_DRIP_TOKENS,
+    },
     ContractError,
 };
 
@@ -15,6 +20,49 @@ pub struct InstantiateMsg {
     /// Duration of a single epoch in seconds for all drip pools.
     /// Examples https://www.nexcess.net/web-tools/unix-timestamp-converter/
     pub epoch_duration: u64,
+    /// Liquid staking contract used to restake native rewards. When not set
+    /// rewards are restaked through authz.
+    pub liquid_staking: Option<String>,
+    /// Allows anyone to create a drip pool depositing its tokens. When not set
+    /// only the owner can create drip pools.
+    pub sponsorship: Option<SponsorshipConfig>,
+    /// Look up the bank metadata of native drip tokens through a Stargate
+    /// query. Enable only if the chain allows it. Defaults to false.
+    pub denom_metadata: Option<bool>,
+    /// Source of the participants eligibility and weight. Defaults to native
+    /// staking.
+    pub weight_source: Option<UncheckedWeightSource>,
+    /// Code id of a cw20-base contract. When set, the shares of every new drip
+    /// pool are minted as a cw20 receipt token.
+    pub receipt_token_code_id: Option<u64>,
+}
+
+/// Liquid staking token source that has to be validated
+#[cw_serde]
+pub struct UncheckedLstSource {
+    pub token: UncheckedLstToken,
+    pub rate: UncheckedRedemptionRate,
+}
+
+#[cw_serde]
+pub enum UncheckedLstToken {
+    Native { denom: String },
+    Cw20 { address: String },
+}
+
+#[cw_serde]
+pub enum UncheckedRedemptionRate {
+    Fixed { rate: Decimal },
+    Query { contract: String, msg: Binary },
+}
+
+/// Weight source that has to be validated
+#[cw_serde]
+pub enum UncheckedWeightSource {
+    NativeStaking {},
+    DaoVotingModule { address: String },
+    Cw20Stake { address: String },
+    Cw4Group { address: String },
 }
 
 /// Drip token that has to be validated
@@ -45,19 +93,224 @@ pub enum ExecuteMsg {
     /// Remove participation to the drip distribution. No more shares
     /// will be accrued.
     RemoveParticipation {},
-    /// Create a distribution drip pool
+    /// Create a distribution drip pool. Sponsors other than the owner must
+    /// attach the initial amount plus the creation fee.
     CreateDripPool {
         token_info: UncheckedDripToken,
         tokens_per_epoch: Uint128,
         epochs_number: u64,
+        /// Duration of the linear unlock of withdrawn tokens
+        vesting: Option<Duration>,
+        /// Share of the withdrawn tokens forfeited when withdrawing before
+        /// the final epoch
+        early_exit_penalty: Option<Decimal>,
+        /// Tokens released at every epoch. Defaults to `tokens_per_epoch`
+        /// tokens at every epoch.
+        emission_schedule: Option<EmissionSchedule>,
+        /// Time after which the pool starts dripping. Defaults to the next
+        /// distribution.
+        start_time: Option<Timestamp>,
+        /// Time from which the pool stops dripping even if epochs are left
+        end_time: Option<Timestamp>,
     },
     UpdateDripPool {},
-    RemoveDripPool {},
+    /// Cancel a drip pool refunding the tokens not yet distributed
+    RemoveDripPool {
+        token: String,
+    },
+    /// Temporarily stop a drip pool without consuming its epochs. Its shares
+    /// cannot be withdrawn while paused.
+    PauseDripPool {
+        token: String,
+    },
+    /// Restart a paused drip pool
+    ResumeDripPool {
+        token: String,
+    },
+    /// Extend a constant emission drip pool by depositing native tokens for
+    /// the additional epochs. Only the pool creator can top it up.
+    TopUpDripPool {
+        token: String,
+        additional_epochs: u64,
+    },
+    /// Handle cw20 deposits
+    Receive(Cw20ReceiveMsg),
+    /// Allow sponsors to create drip pools of a denom or cw20 address,
+    /// optionally with a minimum initial amount
+    AllowDripToken {
+        token: String,
+        min_initial_amount: Option<Uint128>,
+    },
+    /// Prevent sponsors from creating new drip pools of a token
+    DisallowDripToken {
+        token: String,
+    },
+    /// Count the balances of a liquid staking token as native stake
+    AddLstSource {
+        source: UncheckedLstSource,
+    },
+    /// Stop counting the balances of a liquid staking token
+    RemoveLstSource {
+        token: String,
+    },
     /// Compute and distribute active drip pools shares to
-    /// participants
+    /// participants. Refreshes the stake of every participant
+    /// while liquid staking tokens are counted.
     DistributeShares {},
-    SendShares {},
-    WithdrawTokens {},
+    /// Move settled shares of a drip pool from the sender to another address
+    SendShares {
+        recipient: String,
+        token: String,
+        amount: Uint128,
+    },
+    /// Allow a spender to transfer more shares of a drip pool on behalf of
+    /// the sender. Never expires by default.
+    IncreaseShareAllowance {
+        spender: String,
+        token: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Reduce the shares a spender can transfer, removing the allowance when
+    /// it reaches zero
+    DecreaseShareAllowance {
+        spender: String,
+        token: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Transfer shares of a drip pool on behalf of an owner within the
+    /// allowance given to the sender
+    TransferSharesFrom {
+        owner: String,
+        recipient: String,
+        token: String,
+        amount: Uint128,
+    },
+    /// Burn shares to withdraw the associated tokens. By default all the
+    /// shares of every drip pool are burned.
+    WithdrawTokens {
+        /// Drip tokens to withdraw. Other pools are left untouched.
+        tokens: Option<Vec<String>>,
+        /// Shares to burn, only when a single drip token is selected. All the
+        /// shares by default.
+        shares: Option<Uint128>,
+        /// Address whose shares are burned. Defaults to the sender. Withdrawing
+        /// for another address requires its authorization.
+        owner: Option<String>,
+        /// Address receiving the tokens. Defaults to the owner. Tokens of
+        /// vesting pools always vest for the owner.
+        recipient: Option<String>,
+        /// Validator to which native tokens in the staking denom are delegated
+        /// on behalf of the recipient. Not allowed for vesting pools.
+        restake_to: Option<String>,
+    },
+    /// Burn shares to send the associated tokens to a contract along with a
+    /// message to execute. Cw20 tokens are delivered with a cw20 Send while
+    /// native tokens are attached as funds to the message. Fails for vesting
+    /// drip pools.
+    WithdrawTokensAndCall {
+        contract: String,
+        msg: Binary,
+        tokens: Option<Vec<String>>,
+        shares: Option<Uint128>,
+        owner: Option<String>,
+    },
+    /// Settle the shares accrued by the sender and receive them as receipt
+    /// tokens. Only drip pools with a receipt token are considered.
+    ClaimReceiptTokens {},
+    /// Settle the shares accrued by an address and refresh its stake. Anyone can
+    /// sync any address, defaulting to the sender.
+    Sync {
+        address: Option<String>,
+    },
+    /// Request to count the stake of another address, like a vesting account
+    /// or a multisig, toward the sender
+    LinkAddress {
+        address: String,
+    },
+    /// Confirm the link requested by the primary participant
+    ConfirmLink {
+        primary: String,
+    },
+    /// Remove a link. Can be sent by the primary participant or by the linked
+    /// address.
+    UnlinkAddress {
+        address: String,
+    },
+    /// Settle a page of participants and refresh their stake, removing the ones
+    /// no more eligible. Lets keepers apply undelegations before a distribution.
+    /// Distributions refresh every participant while liquid staking tokens are
+    /// counted.
+    SyncParticipants {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Read a page of the cw4 group members adding them to the participants
+    /// with their weight. Only with the cw4 group weight source.
+    SyncGroupMembers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Apply the membership changes of the cw4 group. Sent by the group once
+    /// the drip contract is registered as its hook.
+    MemberChangedHook(MemberChangedHookMsg),
+    /// Allow an operator to withdraw tokens on behalf of the sender. The
+    /// operator can send the tokens only to the sender or to the optional
+    /// recipient. Never expires by default.
+    Authorize {
+        operator: String,
+        expiry: Option<Expiration>,
+        recipient: Option<String>,
+    },
+    /// Remove an operator authorization
+    Revoke {
+        operator: String,
+    },
+    /// Claim the unlocked tokens of all the sender vesting positions
+    ClaimVested {},
+}
+
+/// Messages sent along with cw20 deposits
+#[cw_serde]
+pub enum ReceiveMsg {
+    /// Create a drip pool of the sent cw20 token. Sponsors other than the
+    /// owner must send the initial amount plus the creation fee.
+    CreateDripPool {
+        initial_amount: Uint128,
+        tokens_per_epoch: Uint128,
+        epochs_number: u64,
+        vesting: Option<Duration>,
+        early_exit_penalty: Option<Decimal>,
+        emission_schedule: Option<EmissionSchedule>,
+        start_time: Option<Timestamp>,
+        end_time: Option<Timestamp>,
+    },
+    /// Extend the drip pool of the sent cw20 token by some epochs. Only the
+    /// pool creator can top it up.
+    TopUpDripPool { additional_epochs: u64 },
+    /// Burn the sent receipt tokens to withdraw the associated tokens
+    WithdrawTokens {
+        recipient: Option<String>,
+        restake_to: Option<String>,
+    },
+}
+
+/// Instantiate message of the cw20-base receipt token of a drip pool
+#[cw_serde]
+pub struct ReceiptTokenInstantiateMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub mint: Option<MinterResponse>,
+}
+
+/// Messages accepted by the liquid staking contract used to restake rewards
+#[cw_serde]
+pub enum LiquidStakingExecuteMsg {
+    /// Bond the attached funds minting the liquid staking token to the receiver
+    Bond { receiver: Option<String> },
 }
 
 #[cw_serde]
@@ -66,21 +319,75 @@ pub enum QueryMsg {
     /// Get the current smart contract config
     #[returns(ConfigResponse)]
     Config {},
-    /// Get the vector of participants
+    /// Get a page of participants
     #[returns(ParticipantsResponse)]
-    Participants {},
+    Participants {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     /// Get the vector of drip tokens denom
     #[returns(DripTokensResponse)]
     DripTokens {},
     /// Get info of a specific drip pool
     #[returns(DripPoolResponse)]
     DripPool { token: String },
-    /// Get all drip pools
+    /// Get a page of drip pools, optionally filtered by status. Filtered pages
+    /// read every pool up to the last matching one.
     #[returns(DripPoolsResponse)]
-    DripPools {},
-    // Get participant shares
+    DripPools {
+        status: Option<PoolStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Get the tokens sponsors can distribute
+    #[returns(AllowedDripTokensResponse)]
+    AllowedDripTokens {},
+    /// Get the liquid staking tokens counted as native stake
+    #[returns(LstSourcesResponse)]
+    LstSources {},
+    /// Get the links of an address
+    #[returns(LinksResponse)]
+    Links { address: String },
+    /// Get a page of the settled and pending shares of a participant. Pools
+    /// without shares are skipped but still read.
     #[returns(ParticipantSharesResponse)]
-    ParticipantShares { address: String },
+    ParticipantShares {
+        address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Get a page of the addresses holding shares of a drip pool with their
+    /// settled and pending shares and token value
+    #[returns(PoolShareholdersResponse)]
+    PoolShareholders {
+        token: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Get the shares of an address in every drip pool along with the tokens
+    /// it would receive withdrawing them now
+    #[returns(ClaimableResponse)]
+    Claimable { address: String },
+    /// Preview the next distribution without executing it. Participants are
+    /// paginated while all the dripping pools are returned.
+    #[returns(SimulateDistributionResponse)]
+    SimulateDistribution {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Get the authorization given by an owner to an operator
+    #[returns(AuthorizationResponse)]
+    Authorization { owner: String, operator: String },
+    /// Get the shares of a drip pool that a spender can transfer for an owner
+    #[returns(ShareAllowanceResponse)]
+    ShareAllowance {
+        owner: String,
+        spender: String,
+        token: String,
+    },
+    /// Get the vesting positions of an address
+    #[returns(VestingPositionsResponse)]
+    VestingPositions { address: String },
 }
 
 // Query response structures
@@ -94,6 +401,82 @@ pub struct ParticipantSharesResponse {
     pub shares: Vec<(String, Uint128)>,
 }
 
+#[cw_serde]
+pub struct PoolShareholdersResponse {
+    pub shareholders: Vec<Shareholder>,
+}
+
+/// Settled and pending shares of an address in a drip pool
+#[cw_serde]
+pub struct Shareholder {
+    pub address: Addr,
+    pub shares: Uint128,
+    /// Tokens withdrawn burning the shares, net of the penalty
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct ClaimableResponse {
+    pub claimable: Vec<ClaimableTokens>,
+}
+
+/// Value of the shares of an address in a drip pool
+#[cw_serde]
+pub struct ClaimableTokens {
+    /// Denom or address of the drip token
+    pub token: String,
+    /// Type and denom of the drip token
+    pub denom: Denom,
+    /// Settled and pending shares
+    pub shares: Uint128,
+    /// Tokens withdrawn burning all the shares, net of the penalty
+    pub amount: Uint128,
+    /// Tokens forfeited for the early exit penalty
+    pub penalty: Uint128,
+    /// Status of the drip pool
+    pub status: PoolStatus,
+}
+
+#[cw_serde]
+pub struct SimulateDistributionResponse {
+    /// Whether the distribution time has come
+    pub ready: bool,
+    /// Time from which shares can be distributed
+    pub next_distribution_time: u64,
+    /// Shares issued by every dripping pool, equal to the total accounted stake
+    pub emitted_shares: Uint128,
+    pub participants: Vec<SimulatedParticipant>,
+    pub drip_pools: Vec<SimulatedDripPool>,
+}
+
+/// Weight of a participant for the next distribution
+#[cw_serde]
+pub struct SimulatedParticipant {
+    pub address: Addr,
+    /// Stake accounted at the last settlement, used by the next distribution
+    /// unless liquid staking tokens are counted, in which case the current
+    /// weight is used
+    pub stake: Uint128,
+    /// Current weight, accounted from the next settlement
+    pub weight: Uint128,
+    /// Whether the current weight differs from the accounted stake. Anyone can
+    /// `Sync` a stale participant to apply its weight before the distribution,
+    /// removing it if no more eligible.
+    pub stale: bool,
+}
+
+/// Effect of the next distribution on a drip pool
+#[cw_serde]
+pub struct SimulatedDripPool {
+    pub token: String,
+    /// Shares to issue
+    pub shares: Uint128,
+    /// Tokens to release
+    pub tokens: Uint128,
+    /// Status after the distribution
+    pub status: PoolStatus,
+}
+
 #[cw_serde]
 pub struct ParticipantsResponse {
     pub participants: Vec<Addr>,
@@ -115,7 +498,86 @@ pub struct DripPoolsResponse {
 }
 
 #[cw_serde]
-pub struct VotingPowerAtHeightReponse {
+pub struct AllowedDripToken {
+    /// Denom or address of the token
+    pub token: String,
+    /// Minimum initial amount of sponsored pools
+    pub min_initial_amount: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct AllowedDripTokensResponse {
+    pub allowed_drip_tokens: Vec<AllowedDripToken>,
+}
+
+#[cw_serde]
+pub struct LinksResponse {
+    /// Primary participant to which the address is linked
+    pub primary: Option<Addr>,
+    /// Addresses linked to the address
+    pub linked: Vec<Addr>,
+    /// Primary participant waiting for the address confirmation
+    pub pending: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct LstSourcesResponse {
+    pub lst_sources: Vec<LstSource>,
+}
+
+/// Response of the redemption rate query of a liquid staking contract. Other
+/// fields are ignored.
+#[derive(serde::Deserialize)]
+pub struct ExchangeRateResponse {
+    pub exchange_rate: Decimal,
+}
+
+#[cw_serde]
+pub struct AuthorizationResponse {
+    pub expiry: Option<Expiration>,
+    pub recipient: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct ShareAllowanceResponse {
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+pub struct VestingPositionsResponse {
+    pub positions: Vec<VestingPosition>,
+    /// Tokens claimable right now for every drip token
+    pub claimable: Vec<(String, Uint128)>,
+}
+
+/// Queries sent to the weight source contracts
+#[cw_serde]
+pub enum WeightSourceQueryMsg {
+    /// Query a DAO DAO voting module
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    /// Query a cw20-stake contract
+    StakedBalanceAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    /// Query a cw4 group
+    Member {
+        addr: String,
+        at_height: Option<u64>,
+    },
+    /// List the members of a cw4 group
+    ListMembers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cw_serde]
+pub struct VotingPowerAtHeightResponse {
     pub power: Uint128,
     pub height: u64,
 }
@@ -126,22 +588,133 @@ pub struct TotalPowerAtHeightResponse {
     pub height: u64,
 }
 
+#[cw_serde]
+pub struct StakedBalanceAtHeightResponse {
+    pub balance: Uint128,
+    pub height: u64,
+}
+
+#[cw_serde]
+pub struct MemberResponse {
+    pub weight: Option<u64>,
+}
+
+#[cw_serde]
+pub struct Member {
+    pub addr: String,
+    pub weight: u64,
+}
+
+#[cw_serde]
+pub struct MemberListResponse {
+    pub members: Vec<Member>,
+}
+
+/// Change of weight of a cw4 group member. A missing weight means the address
+/// is not a member.
+#[cw_serde]
+pub struct MemberDiff {
+    pub key: String,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+/// Hook message sent by a cw4 group when its members change
+#[cw_serde]
+pub struct MemberChangedHookMsg {
+    pub diffs: Vec<MemberDiff>,
+}
+
 #[cw_serde]
 pub struct AddParticipantResponse {
     pub address: Addr,
     pub eligible: bool,
 }
 
+impl UncheckedLstSource {
+    pub fn validate(self, api: &dyn Api) -> StdResult<LstSource> {
+        let token = match self.token {
+            UncheckedLstToken::Native { denom } => LstToken::Native { denom },
+            UncheckedLstToken::Cw20 { address } => LstToken::Cw20 {
+                address: api.addr_validate(&address)?,
+            },
+        };
+        let rate = match self.rate {
+            UncheckedRedemptionRate::Fixed { rate } => RedemptionRate::Fixed { rate },
+            UncheckedRedemptionRate::Query { contract, msg } => RedemptionRate::Query {
+                contract: api.addr_validate(&contract)?,
+                msg,
+            },
+        };
+        Ok(LstSource { token, rate })
+    }
+}
+
+impl UncheckedWeightSource {
+    pub fn validate(self, api: &dyn Api) -> StdResult<WeightSource> {
+        Ok(match self {
+            Self::NativeStaking {} => WeightSource::NativeStaking {},
+            Self::DaoVotingModule { address } => WeightSource::DaoVotingModule {
+                address: api.addr_validate(&address)?,
+            },
+            Self::Cw20Stake { address } => WeightSource::Cw20Stake {
+                address: api.addr_validate(&address)?,
+            },
+            Self::Cw4Group { address } => WeightSource::Cw4Group {
+                address: api.addr_validate(&address)?,
+            },
+        })
+    }
+}
+
 impl UncheckedDripToken {
+    /// Denom or address of the token
+    pub fn token(&self) -> String {
+        match self {
+            Self::Native { denom, .. } => denom.clone(),
+            Self::Cw20 { address, .. } => address.clone(),
+        }
+    }
+
+    pub fn initial_amount(&self) -> Uint128 {
+        match self {
+            Self::Native { initial_amount, .. } | Self::Cw20 { initial_amount, .. } => {
+                *initial_amount
+            }
+        }
+    }
+
     /// The function wil:
-    /// 1. check if initial amount is not zero;
-    /// 2. check if the contract has the specificed initial amount;
-    pub fn validate(self, deps: Deps, env: Env) -> Result<DripToken, ContractError> {
+    /// 1. check if the token is allowed and the initial amount is above its minimum for
+    ///    sponsored pools;
+    /// 2. check the format of native denoms;
+    /// 3. check if initial amount is not zero;
+    /// 4. check if the contract has the specificed initial amount;
+    pub fn validate(
+        self,
+        deps: Deps,
+        env: Env,
+        sponsored: bool,
+    ) -> Result<DripToken, ContractError> {
+        if sponsored {
+            match ALLOWED_DRIP_TOKENS.may_load(deps.storage, self.token())? {
+                None => {
+                    return Err(ContractError::DripTokenNotAllowed {
+                        token: self.token(),
+                    })
+                }
+                Some(Some(min_initial_amount)) if self.initial_amount() < min_initial_amount => {
+                    return Err(ContractError::DripPoolTooSmall { min_initial_amount })
+                }
+                _ => {}
+            }
+        }
         match self {
             Self::Native {
                 denom,
                 initial_amount,
             } => {
+                validate_denom(deps.api, &denom)?;
                 if initial_amount.is_zero() {
                     return Err(ContractError::ZeroTokenPool {});
                 };
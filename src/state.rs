@@ -1,6 +1,13 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, StdError, Uint128, WasmMsg};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{
+    to_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Decimal, Empty, StdError,
+    Timestamp, Uint128, WasmMsg,
+};
+use cw20::Denom;
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, KeyDeserialize, Map, MultiIndex};
+
+use crate::ContractError;
+use cw_utils::{Duration, Expiration};
 
 /// Smart contract configuration parameters
 #[cw_serde]
@@ -13,6 +20,62 @@ pub struct Config {
     pub epoch_duration: u64,
     /// Epoch number of the last distribution
     pub next_distribution_time: u64,
+    /// Liquid staking contract used to restake native rewards when authz
+    /// is not available
+    pub liquid_staking: Option<Addr>,
+    /// Allows anyone to create a drip pool depositing its tokens
+    pub sponsorship: Option<SponsorshipConfig>,
+    /// Whether the chain allows the Stargate query of bank denom metadata
+    pub denom_metadata: bool,
+    /// Source of the participants eligibility and weight
+    pub weight_source: WeightSource,
+    /// Code id of the cw20 contract instantiated as receipt token of every
+    /// new drip pool. Shares are internal only when not set.
+    pub receipt_token_code_id: Option<u64>,
+}
+
+/// Source of the weight of a participant
+#[cw_serde]
+pub enum WeightSource {
+    /// Native tokens delegated to validators
+    NativeStaking {},
+    /// Voting power of a DAO DAO voting module
+    DaoVotingModule { address: Addr },
+    /// Tokens staked in a cw20-stake contract
+    Cw20Stake { address: Addr },
+    /// Weight of a cw4 group member
+    Cw4Group { address: Addr },
+}
+
+/// Rules for drip pools created by addresses other than the owner
+#[cw_serde]
+pub struct SponsorshipConfig {
+    /// Share of the initial amount paid on top of it and forwarded to the owner
+    pub creation_fee: Decimal,
+    /// Minimum initial amount of a sponsored pool
+    pub min_initial_amount: Uint128,
+}
+
+/// Liquid staking token whose balances count as native stake
+#[cw_serde]
+pub struct LstSource {
+    pub token: LstToken,
+    /// Native tokens redeemed by one liquid staking token
+    pub rate: RedemptionRate,
+}
+
+#[cw_serde]
+pub enum LstToken {
+    Native { denom: String },
+    Cw20 { address: Addr },
+}
+
+#[cw_serde]
+pub enum RedemptionRate {
+    /// Rate set by the owner
+    Fixed { rate: Decimal },
+    /// Rate read from the `exchange_rate` field of the response of a smart query
+    Query { contract: Addr, msg: Binary },
 }
 
 /// Drip pool information saved on storage
@@ -24,7 +87,8 @@ pub struct DripPool {
     pub initial_amount: Uint128,
     /// Tokens that can be withdrawed
     pub withdrawable_tokens: Uint128,
-    /// Tokens to distribute at every epoch
+    /// Tokens to distribute at every epoch. Starting amount for decaying
+    /// emission schedules.
     pub tokens_per_epoch: Uint128,
     /// Shares issued to participants
     pub issued_shares: Uint128,
@@ -33,6 +97,103 @@ pub struct DripPool {
     /// Current distribution epoch. After distributing the first time
     /// epoch will be 1 and so on.
     pub epoch: u64,
+    /// Shares issued for every unit of stake since the pool creation. Every
+    /// distribution issues one share per staked token.
+    pub shares_per_stake: Uint128,
+    /// Duration of the linear unlock of withdrawn tokens. Tokens are sent
+    /// immediately if not set.
+    pub vesting: Option<Duration>,
+    /// Share of the withdrawn tokens forfeited when withdrawing before the
+    /// final epoch. Forfeited tokens remain in the pool.
+    pub early_exit_penalty: Option<Decimal>,
+    /// Tokens released at every epoch
+    pub emission_schedule: EmissionSchedule,
+    /// Time after which the pool starts dripping. The pool is pending until then.
+    pub start_time: Option<Timestamp>,
+    /// Time from which the pool stops dripping even if epochs are left
+    pub end_time: Option<Timestamp>,
+    /// Lifecycle status of the pool
+    pub status: PoolStatus,
+    /// Address that created the pool and receives the refund if cancelled
+    pub creator: Addr,
+    /// Display name and decimals of the drip token, when available
+    pub metadata: Option<TokenMetadata>,
+    /// Cw20 minted for every issued share. Its supply equals `issued_shares`.
+    pub receipt_token: Option<Addr>,
+}
+
+/// Information used by frontends to display drip token amounts
+#[cw_serde]
+pub struct TokenMetadata {
+    /// Display denom or cw20 symbol
+    pub display: String,
+    /// Decimals of the display denom
+    pub decimals: u32,
+}
+
+/// Lifecycle status of a drip pool
+#[cw_serde]
+pub enum PoolStatus {
+    /// Waiting for the start time
+    Pending,
+    /// Dripping at every distribution
+    Active,
+    /// Temporarily not dripping
+    Paused,
+    /// All epochs distributed or end time reached. Shares can still be withdrawn.
+    Finished,
+    /// Removed before the end. Shares can still be withdrawn.
+    Cancelled,
+    /// Finished with all shares withdrawn
+    Drained,
+}
+
+/// Amount of tokens released by a drip pool at every epoch
+#[cw_serde]
+pub enum EmissionSchedule {
+    /// Release `tokens_per_epoch` tokens at every epoch
+    Constant {},
+    /// Start from `tokens_per_epoch` tokens and release `decrement` tokens
+    /// less at every epoch
+    LinearDecay { decrement: Uint128 },
+    /// Start from `tokens_per_epoch` tokens and halve the amount every
+    /// `interval` epochs
+    Halving { interval: u64 },
+    /// Release an explicit amount of tokens at every epoch. `tokens_per_epoch`
+    /// is ignored.
+    Custom { amounts: Vec<Uint128> },
+}
+
+/// Withdrawn tokens unlocking linearly
+#[cw_serde]
+pub struct VestingPosition {
+    /// Drip token of the pool the tokens were withdrawn from
+    pub token: String,
+    /// Total vesting tokens
+    pub amount: Uint128,
+    /// Tokens already claimed
+    pub claimed: Uint128,
+    /// Block height at the creation of the position
+    pub start_height: u64,
+    /// Block time at the creation of the position
+    pub start_time: Timestamp,
+    /// Duration of the linear unlock
+    pub duration: Duration,
+}
+
+/// Authorization given by an owner to an operator to withdraw on its behalf
+#[cw_serde]
+pub struct Authorization {
+    pub expiry: Expiration,
+    /// Address, besides the owner, to which the operator can send the tokens
+    pub recipient: Option<Addr>,
+}
+
+/// Shares of a drip pool that a spender can transfer on behalf of an owner
+#[cw_serde]
+pub struct ShareAllowance {
+    pub amount: Uint128,
+    pub expires: Expiration,
 }
 
 /// Drip token variants after basic checks
@@ -45,25 +206,156 @@ pub enum DripToken {
 // Configuration.
 pub const CONFIG: Item<Config> = Item::new("config");
 
-// All participants to the drip
-pub const PARTICIPANTS: Item<Vec<Addr>> = Item::new("participants");
+// All participants to the drip with the stake accounted at their last settlement
+pub const PARTICIPANTS: Map<&Addr, Uint128> = Map::new("participants");
+
+// Sum of the stake of all participants
+pub const TOTAL_STAKE: Item<Uint128> = Item::new("total_stake");
 
 // All drip tokens of active drip pools
 pub const DRIP_TOKENS: Item<Vec<String>> = Item::new("drip_tokens");
 
-// Participants shares of every drip pool
-pub const PARTICIPANTS_SHARES: Map<(&Addr, String), Uint128> = Map::new("participants_shares");
+// Indexes of the participants shares
+pub struct SharesIndexes<'a> {
+    // Drip pools in which every address holds shares
+    pub owner: MultiIndex<'a, Addr, Uint128, (String, &'a Addr)>,
+}
+
+impl<'a> IndexList<Uint128> for SharesIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Uint128>> + '_> {
+        let v: Vec<&dyn Index<Uint128>> = vec![&self.owner];
+        Box::new(v.into_iter())
+    }
+}
+
+// Participants shares of every drip pool by drip token and address, so that the
+// shareholders of a pool are ordered by address like the participants. Indexed by address.
+pub fn participants_shares<'a>() -> IndexedMap<'a, (String, &'a Addr), Uint128, SharesIndexes<'a>> {
+    let indexes = SharesIndexes {
+        owner: MultiIndex::new(
+            |pk, _| {
+                // Keys are always written as (token, address)
+                let (_, address) = <(String, &Addr)>::from_slice(pk).unwrap();
+                address
+            },
+            "participants_shares",
+            "participants_shares__owner",
+        ),
+    };
+    IndexedMap::new("participants_shares", indexes)
+}
+
+// Drip pool shares per stake observed by every participant at their last settlement
+pub const PARTICIPANTS_INDEX: Map<(&Addr, String), Uint128> = Map::new("participants_index");
+
+// Drip tokens of the pools that stopped dripping, by closing sequence number
+pub const CLOSED_POOLS: Map<u64, String> = Map::new("closed_pools");
+
+// Number of pool closings, used as the next closing sequence number
+pub const CLOSED_POOLS_COUNT: Item<u64> = Item::new("closed_pools_count");
+
+// Drip pool shares per stake at every closing of the pool
+pub const POOL_CLOSINGS: Map<(String, u64), Uint128> = Map::new("pool_closings");
+
+// Number of pool closings at the last settlement of every address. Pools closed
+// before it have already been settled and are skipped.
+pub const SETTLEMENT_CURSORS: Map<&Addr, u64> = Map::new("settlement_cursors");
+
+// Authorizations given by an owner to an operator to withdraw on its behalf
+pub const AUTHORIZATIONS: Map<(&Addr, &Addr), Authorization> = Map::new("authorizations");
+
+// Shares of a drip pool that an owner allows a spender to transfer
+pub const SHARE_ALLOWANCES: Map<(&Addr, &Addr, String), ShareAllowance> =
+    Map::new("share_allowances");
+
+// Vesting positions of every address
+pub const VESTING_POSITIONS: Map<&Addr, Vec<VestingPosition>> = Map::new("vesting_positions");
+
+// Tokens that sponsors can distribute along with the optional minimum initial amount
+pub const ALLOWED_DRIP_TOKENS: Map<String, Option<Uint128>> = Map::new("allowed_drip_tokens");
+
+// Links requested by a primary participant waiting for the confirmation of the linked address
+pub const PENDING_LINKS: Map<&Addr, Addr> = Map::new("pending_links");
+
+// Primary participant of every linked address
+pub const LINKED_ADDRESSES: Map<&Addr, Addr> = Map::new("linked_addresses");
+
+// Addresses linked to every primary participant
+pub const PRIMARY_LINKS: Map<(&Addr, &Addr), Empty> = Map::new("primary_links");
+
+// Liquid staking tokens counted as native stake, by denom or cw20 address
+pub const LST_SOURCES: Map<String, LstSource> = Map::new("lst_sources");
 
 // Drip pools info
 pub const DRIP_POOLS: Map<String, DripPool> = Map::new("drip_pools");
 
+// Drip token of the pool whose receipt token is being instantiated
+pub const PENDING_RECEIPT_TOKEN: Item<String> = Item::new("pending_receipt_token");
+
+// Drip token of the pool of every receipt token
+pub const RECEIPT_TOKENS: Map<&Addr, String> = Map::new("receipt_tokens");
+
+impl LstToken {
+    /// Denom or address of the token
+    pub fn get_token(&self) -> String {
+        match self {
+            LstToken::Native { denom } => denom.clone(),
+            LstToken::Cw20 { address } => address.to_string(),
+        }
+    }
+}
+
 impl DripPool {
     /// Given an amount of shares computes the associated tokens and remove both tokens
-    /// and shares from the pool.
-    pub fn remove_tokens_and_shares(&mut self, shares: Uint128) {
+    /// and shares from the pool. Tokens forfeited for the early exit penalty remain in the
+    /// pool. Returns the withdrawn tokens and the penalty.
+    pub fn remove_tokens_and_shares(&mut self, shares: Uint128) -> (Uint128, Uint128) {
         let tokens = self.tokens_from_shares(shares);
-        self.withdrawable_tokens -= tokens;
+        let penalty = self.early_exit_penalty(tokens);
+        self.withdrawable_tokens -= tokens - penalty;
         self.issued_shares -= shares;
+        (tokens - penalty, penalty)
+    }
+
+    /// Compute the tokens forfeited when withdrawing before the final epoch
+    pub fn early_exit_penalty(&self, tokens: Uint128) -> Uint128 {
+        match self.early_exit_penalty {
+            Some(penalty) if self.is_dripping() => tokens * penalty,
+            _ => Uint128::zero(),
+        }
+    }
+
+    /// Check if the pool still has epochs to distribute
+    pub fn is_dripping(&self) -> bool {
+        matches!(
+            self.status,
+            PoolStatus::Pending | PoolStatus::Active | PoolStatus::Paused
+        )
+    }
+
+    /// Mark a finished pool as drained once all shares are withdrawn. Cancelled pools
+    /// stay cancelled.
+    pub fn update_drained_status(&mut self) {
+        if self.status == PoolStatus::Finished && self.issued_shares.is_zero() {
+            self.status = PoolStatus::Drained;
+        }
+    }
+
+    pub fn add_available_tokens(&mut self, tokens: Uint128) {
+        match self.drip_token.clone() {
+            DripToken::Native { denom, amount } => {
+                self.drip_token = DripToken::Native {
+                    denom,
+                    amount: amount + tokens,
+                };
+            }
+            DripToken::CW20 { address, amount } => {
+                self.drip_token = DripToken::CW20 {
+                    address,
+                    amount: amount + tokens,
+                }
+            }
+        }
     }
 
     pub fn remove_available_tokens(&mut self, tokens: Uint128) {
@@ -83,6 +375,30 @@ impl DripPool {
         }
     }
 
+    /// Check if the pool is waiting for its start time
+    pub fn is_pending(&self, time: &Timestamp) -> bool {
+        matches!(self.start_time, Some(start_time) if *time < start_time)
+    }
+
+    /// Report as active a pending pool whose start time has passed. The stored status is
+    /// updated by the next distribution.
+    pub fn refresh_status(&mut self, time: &Timestamp) {
+        if self.status == PoolStatus::Pending && !self.is_pending(time) {
+            self.status = PoolStatus::Active;
+        }
+    }
+
+    /// Check if the pool reached its end time
+    pub fn is_expired(&self, time: &Timestamp) -> bool {
+        matches!(self.end_time, Some(end_time) if *time >= end_time)
+    }
+
+    /// Tokens released by the pool at its current epoch
+    pub fn epoch_tokens(&self) -> Uint128 {
+        self.emission_schedule
+            .tokens_for_epoch(self.tokens_per_epoch, self.epoch)
+    }
+
     /// Compute tokens associated to a certain amount of shares
     pub fn tokens_from_shares(&self, shares: Uint128) -> Uint128 {
         shares.multiply_ratio(self.withdrawable_tokens, self.issued_shares)
@@ -113,6 +429,133 @@ impl DripPool {
             .into(),
         })
     }
+
+    /// Message sending tokens to a contract along with a message to execute. Cw20 tokens
+    /// use the cw20 Send while native tokens are attached as funds.
+    pub fn call_contract_message(
+        &self,
+        send_amount: Uint128,
+        contract: &Addr,
+        msg: Binary,
+    ) -> Result<CosmosMsg, StdError> {
+        Ok(match self.drip_token.clone() {
+            DripToken::Native { denom, amount: _ } => WasmMsg::Execute {
+                contract_addr: contract.to_string(),
+                msg,
+                funds: vec![Coin {
+                    denom,
+                    amount: send_amount,
+                }],
+            }
+            .into(),
+            DripToken::CW20 { address, amount: _ } => WasmMsg::Execute {
+                contract_addr: address.to_string(),
+                msg: to_binary(&cw20::Cw20ExecuteMsg::Send {
+                    contract: contract.to_string(),
+                    amount: send_amount,
+                    msg,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        })
+    }
+}
+
+impl EmissionSchedule {
+    /// Tokens released at the given epoch, starting from 0
+    pub fn tokens_for_epoch(&self, tokens_per_epoch: Uint128, epoch: u64) -> Uint128 {
+        match self {
+            EmissionSchedule::Constant {} => tokens_per_epoch,
+            EmissionSchedule::LinearDecay { decrement } => {
+                tokens_per_epoch.saturating_sub(decrement.saturating_mul(Uint128::from(epoch)))
+            }
+            EmissionSchedule::Halving { interval } => {
+                let halvings = epoch / interval;
+                if halvings >= 128 {
+                    Uint128::zero()
+                } else {
+                    tokens_per_epoch >> halvings as u32
+                }
+            }
+            EmissionSchedule::Custom { amounts } => {
+                amounts.get(epoch as usize).copied().unwrap_or_default()
+            }
+        }
+    }
+
+    /// Validate the schedule and compute the total amount of tokens released
+    /// over all the epochs
+    pub fn total_amount(
+        &self,
+        tokens_per_epoch: Uint128,
+        epochs_number: u64,
+    ) -> Result<Uint128, ContractError> {
+        let epochs = Uint128::from(epochs_number);
+        match self {
+            EmissionSchedule::Constant {} => Ok(tokens_per_epoch.checked_mul(epochs)?),
+            EmissionSchedule::LinearDecay { decrement } => {
+                // Amounts form an arithmetic series that must not go below zero
+                let last_decrement = decrement.checked_mul(epochs - Uint128::one())?;
+                if last_decrement > tokens_per_epoch {
+                    return Err(ContractError::InvalidEmissionSchedule {});
+                }
+                let decrements = decrement
+                    .checked_mul(epochs)?
+                    .checked_mul(epochs - Uint128::one())?
+                    / Uint128::new(2);
+                Ok(tokens_per_epoch.checked_mul(epochs)? - decrements)
+            }
+            EmissionSchedule::Halving { interval } => {
+                if *interval == 0 {
+                    return Err(ContractError::InvalidEmissionSchedule {});
+                }
+                let mut total = Uint128::zero();
+                let mut amount = tokens_per_epoch;
+                let mut epoch = 0u64;
+                while epoch < epochs_number && !amount.is_zero() {
+                    let interval_epochs = (*interval).min(epochs_number - epoch);
+                    total = total.checked_add(amount.checked_mul(interval_epochs.into())?)?;
+                    amount >>= 1;
+                    epoch += interval_epochs;
+                }
+                Ok(total)
+            }
+            EmissionSchedule::Custom { amounts } => {
+                if amounts.len() as u64 != epochs_number {
+                    return Err(ContractError::InvalidEmissionSchedule {});
+                }
+                Ok(amounts
+                    .iter()
+                    .try_fold(Uint128::zero(), |total, amount| total.checked_add(*amount))?)
+            }
+        }
+    }
+}
+
+impl VestingPosition {
+    /// Compute the tokens unlocked since the creation of the position
+    pub fn vested(&self, block: &BlockInfo) -> Uint128 {
+        let (elapsed, total) = match self.duration {
+            Duration::Height(blocks) => (block.height.saturating_sub(self.start_height), blocks),
+            Duration::Time(seconds) => (
+                block
+                    .time
+                    .seconds()
+                    .saturating_sub(self.start_time.seconds()),
+                seconds,
+            ),
+        };
+        if elapsed >= total {
+            return self.amount;
+        }
+        self.amount.multiply_ratio(elapsed, total)
+    }
+
+    /// Compute the unlocked tokens not yet claimed
+    pub fn claimable(&self, block: &BlockInfo) -> Uint128 {
+        self.vested(block) - self.claimed
+    }
 }
 
 impl DripToken {
@@ -132,4 +575,12 @@ impl DripToken {
             DripToken::CW20 { address, amount: _ } => address.to_string(),
         }
     }
+
+    // Getter for the drip token type and denom
+    pub fn get_denom(&self) -> Denom {
+        match self {
+            DripToken::Native { denom, amount: _ } => Denom::Native(denom.clone()),
+            DripToken::CW20 { address, amount: _ } => Denom::Cw20(address.clone()),
+        }
+    }
 }
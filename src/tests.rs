@@ -2,5 +2,6 @@ mod lab;
 
 mod distribution;
 mod drip_pools;
+mod helpers;
 mod participants;
 mod withdraw;
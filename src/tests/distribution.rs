@@ -2,8 +2,11 @@ use cosmwasm_std::{Addr, Coin, Uint128};
 use cw20::Cw20Coin;
 
 use crate::{
-    msg::UncheckedDripToken,
-    state::{DripPool, DripToken},
+    msg::{
+        DripPoolsResponse, ExecuteMsg, ParticipantSharesResponse, QueryMsg, Shareholder,
+        SimulatedDripPool, SimulatedParticipant, UncheckedDripToken,
+    },
+    state::{DripPool, DripToken, EmissionSchedule, PoolStatus},
     ContractError,
 };
 
@@ -105,6 +108,16 @@ pub fn no_min_staking() {
             issued_shares: Uint128::zero(),
             epochs_number: 10u64,
             epoch: 0u64,
+            shares_per_stake: Uint128::zero(),
+            vesting: None,
+            early_exit_penalty: None,
+            emission_schedule: EmissionSchedule::Constant {},
+            start_time: None,
+            end_time: None,
+            status: PoolStatus::Active,
+            creator: Addr::unchecked(test_lab.owner.clone()),
+            metadata: None,
+            receipt_token: None,
         })
     );
 
@@ -391,3 +404,839 @@ pub fn multiple_drip_pools() {
         ]
     );
 }
+
+#[test]
+pub fn sync_updates_stake() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 2_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // New delegations are not accounted until the participant syncs
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator2".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(2_000_000))]);
+
+    let _resp = test_lab.sync(Addr::unchecked(PAR1)).unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(4_000_000))]);
+
+    let resp = test_lab.query_drip_pool(native);
+    let pool = resp.drip_pool.unwrap();
+    assert_eq!(pool.issued_shares, Uint128::new(4_000_000));
+    assert_eq!(pool.shares_per_stake, Uint128::new(3));
+}
+
+#[test]
+pub fn sync_removes_ineligible_participant() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .sudo_mint_1000(PAR2.to_string(), native.clone(), 1_000u128);
+
+    for participant in [PAR1, PAR2] {
+        _ = test_lab.create_delegation(
+            Addr::unchecked(participant),
+            "validator1".to_string(),
+            Coin {
+                denom: native.clone(),
+                amount: Uint128::new(1_000_000),
+            },
+        );
+        let _resp = test_lab
+            .add_participant(Addr::unchecked(participant))
+            .unwrap();
+    }
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    _ = test_lab.undelegate(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(500_000),
+        },
+    );
+    let _resp = test_lab.sync(Addr::unchecked(PAR1)).unwrap();
+
+    let resp = test_lab.query_participants();
+    assert_eq!(resp.participants, vec![Addr::unchecked(PAR2)]);
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Shares accrued before the removal are kept
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(1_000_000))]);
+
+    let resp = test_lab.query_participant_shares(PAR2.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(2_000_000))]);
+
+    let resp = test_lab.query_drip_pool(native);
+    assert_eq!(
+        resp.drip_pool.map(|pool| pool.issued_shares).unwrap(),
+        Uint128::new(3_000_000)
+    );
+}
+
+#[test]
+pub fn third_party_sync() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .sudo_mint_1000(PAR2.to_string(), native.clone(), 1_000u128);
+
+    for participant in [PAR1, PAR2] {
+        _ = test_lab.create_delegation(
+            Addr::unchecked(participant),
+            "validator1".to_string(),
+            Coin {
+                denom: native.clone(),
+                amount: Uint128::new(1_000_000),
+            },
+        );
+        let _resp = test_lab
+            .add_participant(Addr::unchecked(participant))
+            .unwrap();
+    }
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    _ = test_lab.undelegate(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    // Anyone can refresh the stale stake of a participant
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR3),
+            &ExecuteMsg::Sync {
+                address: Some(PAR1.to_string()),
+            },
+        )
+        .unwrap();
+
+    let resp = test_lab.query_participants();
+    assert_eq!(resp.participants, vec![Addr::unchecked(PAR2)]);
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // The removed participant stops accruing shares
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(1_000_000))]);
+
+    let resp = test_lab.query_participant_shares(PAR2.to_string());
+    assert_eq!(resp.shares, vec![(native, Uint128::new(2_000_000))]);
+}
+
+#[test]
+pub fn settle_closed_drip_pools() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 2u128)
+        .sudo_mint_1000(owner.to_string(), native.clone(), 10u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .sudo_mint_1000(PAR2.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(2_000),
+            },
+            Uint128::new(1_000),
+            2u64,
+            &[],
+        )
+        .unwrap();
+
+    for _ in 0..2 {
+        test_lab.advance_blocks(EPOCH);
+        let _resp = test_lab.distribute_shares().unwrap();
+    }
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.status, PoolStatus::Finished);
+
+    // Joining after the pool closed gives no shares of it
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR2),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR2)).unwrap();
+    let resp = test_lab.query_participant_shares(PAR2.to_string());
+    assert_eq!(resp.shares, vec![]);
+
+    // The closed pool is settled once more and then skipped
+    let _resp = test_lab.sync(Addr::unchecked(PAR1)).unwrap();
+    let _resp = test_lab.sync(Addr::unchecked(PAR1)).unwrap();
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(2_000_000))]);
+
+    // Once reactivated the pool drips to both participants from the closing index
+    let _resp = test_lab
+        .top_up_drip_pool(
+            owner,
+            native.clone(),
+            2,
+            &[Coin::new(2_000, native.clone())],
+        )
+        .unwrap();
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    for participant in [PAR1, PAR2] {
+        let _resp = test_lab.sync(Addr::unchecked(participant)).unwrap();
+    }
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(3_000_000))]);
+    let resp = test_lab.query_participant_shares(PAR2.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(1_000_000))]);
+
+    let pool = test_lab.query_drip_pool(native).drip_pool.unwrap();
+    assert_eq!(pool.issued_shares, Uint128::new(4_000_000));
+}
+
+#[test]
+pub fn emission_schedules() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .init_cw20(vec![Cw20Coin {
+            address: drip_addr,
+            amount: Uint128::new(1_000_000),
+        }]);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    // 1_000 + 700 + 400 + 100
+    let _resp = test_lab
+        .execute_drip(
+            owner.clone(),
+            &ExecuteMsg::CreateDripPool {
+                token_info: UncheckedDripToken::Native {
+                    denom: native.clone(),
+                    initial_amount: Uint128::new(2_200),
+                },
+                tokens_per_epoch: Uint128::new(1_000),
+                epochs_number: 4,
+                vesting: None,
+                early_exit_penalty: None,
+                emission_schedule: Some(EmissionSchedule::LinearDecay {
+                    decrement: Uint128::new(300),
+                }),
+                start_time: None,
+                end_time: None,
+            },
+        )
+        .unwrap();
+
+    // 1_000 + 1_000 + 500 + 500
+    let cw20_address = test_lab.cw20_address.clone();
+    let _resp = test_lab
+        .execute_drip(
+            owner,
+            &ExecuteMsg::CreateDripPool {
+                token_info: UncheckedDripToken::Cw20 {
+                    address: cw20_address.clone(),
+                    initial_amount: Uint128::new(3_000),
+                },
+                tokens_per_epoch: Uint128::new(1_000),
+                epochs_number: 4,
+                vesting: None,
+                early_exit_penalty: None,
+                emission_schedule: Some(EmissionSchedule::Halving { interval: 2 }),
+                start_time: None,
+                end_time: None,
+            },
+        )
+        .unwrap();
+
+    let expected = [
+        (1_000, 1_000),
+        (1_700, 2_000),
+        (2_100, 2_500),
+        (2_200, 3_000),
+    ];
+    for (native_tokens, cw20_tokens) in expected {
+        test_lab.advance_blocks(EPOCH);
+        let _resp = test_lab.distribute_shares().unwrap();
+
+        let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+        assert_eq!(pool.withdrawable_tokens, Uint128::new(native_tokens));
+
+        let pool = test_lab
+            .query_drip_pool(cw20_address.clone())
+            .drip_pool
+            .unwrap();
+        assert_eq!(pool.withdrawable_tokens, Uint128::new(cw20_tokens));
+    }
+
+    let pool = test_lab.query_drip_pool(native).drip_pool.unwrap();
+    assert_eq!(pool.drip_token.get_available_amount(), Uint128::zero());
+}
+
+#[test]
+pub fn delayed_start() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let start_time = test_lab.app.block_info().time.plus_seconds(2 * EPOCH);
+    let _resp = test_lab
+        .execute_drip(
+            owner,
+            &ExecuteMsg::CreateDripPool {
+                token_info: UncheckedDripToken::Native {
+                    denom: native.clone(),
+                    initial_amount: Uint128::new(10_000),
+                },
+                tokens_per_epoch: Uint128::new(1_000),
+                epochs_number: 10,
+                vesting: None,
+                early_exit_penalty: None,
+                emission_schedule: None,
+                start_time: Some(start_time),
+                end_time: None,
+            },
+        )
+        .unwrap();
+
+    // The pool is pending and does not consume an epoch
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.start_time, Some(start_time));
+    assert_eq!(pool.status, PoolStatus::Pending);
+    assert_eq!(pool.epoch, 0);
+    assert_eq!(pool.withdrawable_tokens, Uint128::zero());
+
+    let resp = test_lab.query_drip_pools_by_status(Some(PoolStatus::Pending));
+    assert_eq!(resp.drip_pools.len(), 1);
+
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert!(resp.shares.is_empty());
+
+    // Queries report the pool active once the start time passes
+    test_lab.advance_blocks(EPOCH);
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.status, PoolStatus::Active);
+    let resp = test_lab.query_drip_pools_by_status(Some(PoolStatus::Pending));
+    assert!(resp.drip_pools.is_empty());
+    let resp = test_lab.query_drip_pools_by_status(Some(PoolStatus::Active));
+    assert_eq!(resp.drip_pools.len(), 1);
+
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let pool = test_lab.query_drip_pool(native).drip_pool.unwrap();
+    assert_eq!(pool.epoch, 1);
+    assert_eq!(pool.withdrawable_tokens, Uint128::new(1_000));
+    assert_eq!(pool.issued_shares, Uint128::new(1_000_000));
+}
+
+#[test]
+pub fn end_time() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let now = test_lab.app.block_info().time;
+    let create_msg = |start_time, end_time| ExecuteMsg::CreateDripPool {
+        token_info: UncheckedDripToken::Native {
+            denom: native.clone(),
+            initial_amount: Uint128::new(10_000),
+        },
+        tokens_per_epoch: Uint128::new(1_000),
+        epochs_number: 10,
+        vesting: None,
+        early_exit_penalty: None,
+        emission_schedule: None,
+        start_time,
+        end_time: Some(end_time),
+    };
+
+    let err: ContractError = test_lab
+        .execute_drip(
+            owner.clone(),
+            &create_msg(Some(now.plus_seconds(EPOCH)), now.plus_seconds(EPOCH)),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidEndTime {});
+
+    let _resp = test_lab
+        .execute_drip(
+            owner.clone(),
+            &create_msg(None, now.plus_seconds(2 * EPOCH + 1)),
+        )
+        .unwrap();
+    let owner_balance = test_lab.query_balance(owner.to_string());
+
+    for _ in 0..3 {
+        test_lab.advance_blocks(EPOCH);
+        let _resp = test_lab.distribute_shares().unwrap();
+    }
+
+    // The pool stops dripping after the end time and refunds the tokens left
+    let pool = test_lab.query_drip_pool(native).drip_pool.unwrap();
+    assert_eq!(pool.epoch, 2);
+    assert_eq!(pool.status, PoolStatus::Finished);
+    assert_eq!(pool.withdrawable_tokens, Uint128::new(2_000));
+    assert_eq!(pool.drip_token.get_available_amount(), Uint128::zero());
+
+    let resp = test_lab.query_balance(owner.to_string());
+    assert_eq!(resp, owner_balance + Uint128::new(8_000));
+
+    let resp = test_lab.query_drip_tokens();
+    assert!(resp.drip_tokens.is_empty());
+}
+
+#[test]
+fn simulate_distribution() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .sudo_mint_1000(PAR2.to_string(), native.clone(), 1_000u128);
+
+    let delegation = Coin {
+        denom: native.clone(),
+        amount: Uint128::new(1_000_000),
+    };
+    for participant in [PAR1, PAR2] {
+        _ = test_lab.create_delegation(
+            Addr::unchecked(participant),
+            "validator1".to_string(),
+            delegation.clone(),
+        );
+        let _resp = test_lab
+            .add_participant(Addr::unchecked(participant))
+            .unwrap();
+    }
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    let resp = test_lab.query_simulate_distribution(None, None);
+    assert!(!resp.ready);
+
+    // The stake accounted at the last settlement is used by the distribution
+    test_lab.undelegate(Addr::unchecked(PAR2), "validator1".to_string(), delegation);
+    test_lab.advance_blocks(EPOCH);
+    let resp = test_lab.query_simulate_distribution(None, None);
+    assert!(resp.ready);
+    assert_eq!(resp.emitted_shares, Uint128::new(2_000_000));
+    assert_eq!(
+        resp.participants,
+        vec![
+            SimulatedParticipant {
+                address: Addr::unchecked(PAR1),
+                stake: Uint128::new(1_000_000),
+                weight: Uint128::new(1_000_000),
+                stale: false,
+            },
+            SimulatedParticipant {
+                address: Addr::unchecked(PAR2),
+                stake: Uint128::new(1_000_000),
+                weight: Uint128::zero(),
+                stale: true,
+            },
+        ]
+    );
+    assert_eq!(
+        resp.drip_pools,
+        vec![SimulatedDripPool {
+            token: native.clone(),
+            shares: Uint128::new(2_000_000),
+            tokens: Uint128::new(1_000),
+            status: PoolStatus::Active,
+        }]
+    );
+
+    let resp = test_lab.query_simulate_distribution(Some(PAR1.to_string()), Some(1));
+    assert_eq!(resp.participants.len(), 1);
+    assert_eq!(resp.participants[0].address, Addr::unchecked(PAR2));
+
+    // The simulation does not change the state
+    let _resp = test_lab.distribute_shares().unwrap();
+    let drip_pool = test_lab.query_drip_pool(native).drip_pool.unwrap();
+    assert_eq!(drip_pool.issued_shares, Uint128::new(2_000_000));
+    assert_eq!(drip_pool.withdrawable_tokens, Uint128::new(1_000));
+
+    // Syncing the stale participant removes it before the next distribution
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR3),
+            &ExecuteMsg::Sync {
+                address: Some(PAR2.to_string()),
+            },
+        )
+        .unwrap();
+    test_lab.advance_blocks(EPOCH);
+    let resp = test_lab.query_simulate_distribution(None, None);
+    assert_eq!(resp.emitted_shares, Uint128::new(1_000_000));
+    assert_eq!(
+        resp.participants,
+        vec![SimulatedParticipant {
+            address: Addr::unchecked(PAR1),
+            stake: Uint128::new(1_000_000),
+            weight: Uint128::new(1_000_000),
+            stale: false,
+        }]
+    );
+}
+
+#[test]
+fn paginated_queries() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .init_cw20(vec![Cw20Coin {
+            address: drip_addr,
+            amount: Uint128::new(1_000_000),
+        }]);
+    let cw20_address = test_lab.cw20_address.clone();
+
+    for participant in [PAR1, PAR2, PAR3] {
+        test_lab = test_lab.sudo_mint_1000(participant.to_string(), native.clone(), 1_000u128);
+        _ = test_lab.create_delegation(
+            Addr::unchecked(participant),
+            "validator1".to_string(),
+            Coin {
+                denom: native.clone(),
+                amount: Uint128::new(1_000_000),
+            },
+        );
+        let _resp = test_lab
+            .add_participant(Addr::unchecked(participant))
+            .unwrap();
+    }
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Cw20 {
+                address: cw20_address.clone(),
+                initial_amount: Uint128::new(50_000),
+            },
+            Uint128::new(25_000),
+            2u64,
+            &[],
+        )
+        .unwrap();
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let resp = test_lab.query_participants_page(None, Some(2));
+    assert_eq!(
+        resp.participants,
+        vec![Addr::unchecked(PAR1), Addr::unchecked(PAR2)]
+    );
+    let resp = test_lab.query_participants_page(Some(PAR2.to_string()), Some(2));
+    assert_eq!(resp.participants, vec![Addr::unchecked(PAR3)]);
+
+    let resp: DripPoolsResponse = test_lab
+        .app
+        .wrap()
+        .query_wasm_smart(
+            test_lab.drip_address.clone(),
+            &QueryMsg::DripPools {
+                status: None,
+                start_after: Some(cw20_address.clone()),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(resp.drip_pools.len(), 1);
+    assert_eq!(resp.drip_pools[0].drip_token.get_token(), native);
+
+    let resp: ParticipantSharesResponse = test_lab
+        .app
+        .wrap()
+        .query_wasm_smart(
+            test_lab.drip_address.clone(),
+            &QueryMsg::ParticipantShares {
+                address: PAR3.to_string(),
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        resp.shares,
+        vec![(cw20_address.clone(), Uint128::new(1_000_000))]
+    );
+
+    let _resp = test_lab.sync(Addr::unchecked(PAR1)).unwrap();
+    let resp = test_lab.query_pool_shareholders(native.clone(), None, Some(2));
+    assert_eq!(
+        resp.shareholders,
+        vec![
+            Shareholder {
+                address: Addr::unchecked(PAR1),
+                shares: Uint128::new(1_000_000),
+                amount: Uint128::new(333),
+            },
+            Shareholder {
+                address: Addr::unchecked(PAR2),
+                shares: Uint128::new(1_000_000),
+                amount: Uint128::new(333),
+            },
+        ]
+    );
+    let resp = test_lab.query_pool_shareholders(native, Some(PAR2.to_string()), Some(2));
+    assert_eq!(resp.shareholders.len(), 1);
+    assert_eq!(resp.shareholders[0].address, Addr::unchecked(PAR3));
+
+    // Withdrawn shares are no more listed
+    let _resp = test_lab
+        .withdraw_tokens_partial(
+            Addr::unchecked(PAR1),
+            Some(vec![cw20_address.clone()]),
+            None,
+        )
+        .unwrap();
+    let resp = test_lab.query_pool_shareholders(cw20_address, None, None);
+    assert_eq!(resp.shareholders.len(), 2);
+    assert_eq!(resp.shareholders[0].address, Addr::unchecked(PAR2));
+    assert_eq!(resp.shareholders[0].amount, Uint128::new(8_333));
+}
+
+#[test]
+fn pool_shareholders() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab.sudo_mint_1000(drip_addr, native.clone(), 100u128);
+
+    for participant in [PAR1, PAR2, PAR3] {
+        test_lab = test_lab.sudo_mint_1000(participant.to_string(), native.clone(), 1_000u128);
+        _ = test_lab.create_delegation(
+            Addr::unchecked(participant),
+            "validator1".to_string(),
+            Coin {
+                denom: native.clone(),
+                amount: Uint128::new(1_000_000),
+            },
+        );
+        let _resp = test_lab
+            .add_participant(Addr::unchecked(participant))
+            .unwrap();
+    }
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+    let _resp = test_lab.sync(Addr::unchecked(PAR1)).unwrap();
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Participants joining after the distribution hold no shares
+    let late = "participant0";
+    test_lab = test_lab.sudo_mint_1000(late.to_string(), native.clone(), 1_000u128);
+    _ = test_lab.create_delegation(
+        Addr::unchecked(late),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(late)).unwrap();
+
+    // Settled shares are listed along with the shares accrued since the last settlement
+    let shareholder = |address: &str| Shareholder {
+        address: Addr::unchecked(address),
+        shares: Uint128::new(2_000_000),
+        amount: Uint128::new(666),
+    };
+    let resp = test_lab.query_pool_shareholders(native.clone(), None, Some(1));
+    assert_eq!(resp.shareholders, vec![shareholder(PAR1)]);
+
+    // Participants that never settled shares of the pool are listed too
+    let resp = test_lab.query_pool_shareholders(native.clone(), Some(PAR1.to_string()), Some(1));
+    assert_eq!(resp.shareholders, vec![shareholder(PAR2)]);
+    let resp = test_lab.query_pool_shareholders(native.clone(), Some(PAR2.to_string()), Some(1));
+    assert_eq!(resp.shareholders, vec![shareholder(PAR3)]);
+
+    // Former participants keep their settled shares
+    let _resp = test_lab.remove_participant(Addr::unchecked(PAR2)).unwrap();
+    let resp = test_lab.query_pool_shareholders(native, None, None);
+    assert_eq!(
+        resp.shareholders,
+        vec![shareholder(PAR1), shareholder(PAR2), shareholder(PAR3)]
+    );
+}
@@ -1,14 +1,14 @@
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{to_binary, Addr, Coin, Decimal, Uint128};
 use cw20::Cw20Coin;
 use cw_multi_test::Executor;
 
 use crate::{
-    msg::{ExecuteMsg, UncheckedDripToken},
-    state::{DripPool, DripToken},
+    msg::{AllowedDripToken, ExecuteMsg, ReceiveMsg, UncheckedDripToken},
+    state::{DripPool, DripToken, EmissionSchedule, PoolStatus, SponsorshipConfig, TokenMetadata},
     ContractError,
 };
 
-use super::lab::LabBuilder;
+use super::lab::{LabBuilder, EPOCH, PAR1, SPONSOR};
 
 #[test]
 pub fn drip_pool_basic_checks() {
@@ -34,6 +34,11 @@ pub fn drip_pool_basic_checks() {
                 },
                 tokens_per_epoch: Uint128::zero(),
                 epochs_number: 10u64,
+                vesting: None,
+                early_exit_penalty: None,
+                emission_schedule: None,
+                start_time: None,
+                end_time: None,
             },
             &[],
         )
@@ -55,6 +60,11 @@ pub fn drip_pool_basic_checks() {
                 },
                 tokens_per_epoch: Uint128::zero(),
                 epochs_number: 0u64,
+                vesting: None,
+                early_exit_penalty: None,
+                emission_schedule: None,
+                start_time: None,
+                end_time: None,
             },
             &[],
         )
@@ -273,6 +283,16 @@ fn funded_contract() {
             issued_shares: Uint128::zero(),
             epochs_number: 10u64,
             epoch: 0u64,
+            shares_per_stake: Uint128::zero(),
+            vesting: None,
+            early_exit_penalty: None,
+            emission_schedule: EmissionSchedule::Constant {},
+            start_time: None,
+            end_time: None,
+            status: PoolStatus::Active,
+            creator: Addr::unchecked(test_lab.owner.clone()),
+            metadata: None,
+            receipt_token: None,
         })
     );
 
@@ -310,6 +330,19 @@ fn funded_contract() {
             issued_shares: Uint128::zero(),
             epochs_number: 10u64,
             epoch: 0u64,
+            shares_per_stake: Uint128::zero(),
+            vesting: None,
+            early_exit_penalty: None,
+            emission_schedule: EmissionSchedule::Constant {},
+            start_time: None,
+            end_time: None,
+            status: PoolStatus::Active,
+            creator: Addr::unchecked(test_lab.owner.clone()),
+            metadata: Some(TokenMetadata {
+                display: "PYT".to_string(),
+                decimals: 6,
+            }),
+            receipt_token: None,
         })
     );
 }
@@ -354,3 +387,821 @@ fn drip_pool_already_exists() {
 
     assert_eq!(err, ContractError::DripPoolAlreadyExists {})
 }
+
+#[test]
+fn invalid_emission_schedule() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab.sudo_mint_1000(drip_addr, native.clone(), 1_000u128);
+
+    let create_msg =
+        |initial_amount: u128, epochs_number, emission_schedule| ExecuteMsg::CreateDripPool {
+            token_info: UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(initial_amount),
+            },
+            tokens_per_epoch: Uint128::new(1_000),
+            epochs_number,
+            vesting: None,
+            early_exit_penalty: None,
+            emission_schedule: Some(emission_schedule),
+            start_time: None,
+            end_time: None,
+        };
+
+    // Amounts would go below zero
+    let err: ContractError = test_lab
+        .execute_drip(
+            owner.clone(),
+            &create_msg(
+                1_000,
+                3,
+                EmissionSchedule::LinearDecay {
+                    decrement: Uint128::new(600),
+                },
+            ),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidEmissionSchedule {});
+
+    let err: ContractError = test_lab
+        .execute_drip(
+            owner.clone(),
+            &create_msg(1_000, 3, EmissionSchedule::Halving { interval: 0 }),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidEmissionSchedule {});
+
+    // One amount for every epoch is required
+    let err: ContractError = test_lab
+        .execute_drip(
+            owner.clone(),
+            &create_msg(
+                1_000,
+                3,
+                EmissionSchedule::Custom {
+                    amounts: vec![Uint128::new(500), Uint128::new(500)],
+                },
+            ),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidEmissionSchedule {});
+
+    // The schedule total must coincide with the initial amount
+    let err: ContractError = test_lab
+        .execute_drip(
+            owner,
+            &create_msg(3_000, 3, EmissionSchedule::Halving { interval: 2 }),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::WrongTokensAmount {
+            tokens_amount: Uint128::new(3_000),
+            total_tokens: Uint128::new(2_500)
+        }
+    );
+}
+
+#[test]
+fn pool_status_lifecycle() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(2_000),
+            },
+            Uint128::new(1_000),
+            2u64,
+            &[],
+        )
+        .unwrap();
+
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.status, PoolStatus::Active);
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.status, PoolStatus::Active);
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Finished pools are no more dripping but are still listed
+    assert!(test_lab.query_drip_tokens().drip_tokens.is_empty());
+    let pools = test_lab.query_drip_pools().drip_pools;
+    assert_eq!(pools.len(), 1);
+    assert_eq!(pools[0].status, PoolStatus::Finished);
+    let pools = test_lab
+        .query_drip_pools_by_status(Some(PoolStatus::Active))
+        .drip_pools;
+    assert!(pools.is_empty());
+
+    // Withdrawing all the shares drains the pool
+    let _resp = test_lab.withdraw_tokens(Addr::unchecked(PAR1)).unwrap();
+    let pools = test_lab
+        .query_drip_pools_by_status(Some(PoolStatus::Drained))
+        .drip_pools;
+    assert_eq!(pools.len(), 1);
+    assert_eq!(pools[0].drip_token.get_token(), native);
+}
+
+#[test]
+fn remove_drip_pool() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let remove_msg = ExecuteMsg::RemoveDripPool {
+        token: native.clone(),
+    };
+
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &remove_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Tokens not yet distributed are refunded to the owner
+    let _resp = test_lab.execute_drip(owner.clone(), &remove_msg).unwrap();
+    assert_eq!(
+        test_lab.query_balance(owner.to_string()),
+        Uint128::new(9_000)
+    );
+    assert!(test_lab.query_drip_tokens().drip_tokens.is_empty());
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.status, PoolStatus::Cancelled);
+
+    // Shares issued before the removal can still be withdrawn and the pool stays cancelled
+    let _resp = test_lab.withdraw_tokens(Addr::unchecked(PAR1)).unwrap();
+    assert_eq!(test_lab.query_balance(PAR1.into()), Uint128::new(1_000));
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.status, PoolStatus::Cancelled);
+
+    let err: ContractError = test_lab
+        .execute_drip(owner.clone(), &remove_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::InactiveDripPool {
+            token: native.clone()
+        }
+    );
+
+    // Cancelled pools cannot be reactivated
+    let err: ContractError = test_lab
+        .top_up_drip_pool(
+            owner,
+            native.clone(),
+            2,
+            &[Coin::new(2_000, native.clone())],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InactiveDripPool { token: native });
+}
+
+#[test]
+fn pause_drip_pool() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .init_cw20(vec![Cw20Coin {
+            address: drip_addr,
+            amount: Uint128::new(1_000_000),
+        }]);
+    let cw20 = test_lab.cw20_address.clone();
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Cw20 {
+                address: cw20.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let pause_msg = ExecuteMsg::PauseDripPool {
+        token: cw20.clone(),
+    };
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &pause_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let _resp = test_lab.execute_drip(owner.clone(), &pause_msg).unwrap();
+    let err: ContractError = test_lab
+        .execute_drip(owner.clone(), &pause_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::PausedDripPool {
+            token: cw20.clone()
+        }
+    );
+
+    // Only the native pool drips while the cw20 pool is paused
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.epoch, 2);
+    let pool = test_lab.query_drip_pool(cw20.clone()).drip_pool.unwrap();
+    assert_eq!(pool.epoch, 1);
+    assert_eq!(pool.status, PoolStatus::Paused);
+
+    // Paused pools are skipped by default and cannot be selected
+    let _resp = test_lab.withdraw_tokens(Addr::unchecked(PAR1)).unwrap();
+    assert_eq!(test_lab.query_balance(PAR1.into()), Uint128::new(2_000));
+    assert_eq!(test_lab.query_cw20_balance(PAR1.into()), Uint128::zero());
+
+    let err: ContractError = test_lab
+        .withdraw_tokens_partial(Addr::unchecked(PAR1), Some(vec![cw20.clone()]), None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::PausedDripPool {
+            token: cw20.clone()
+        }
+    );
+
+    // The resumed pool restarts from the epoch at which it was paused
+    let _resp = test_lab
+        .execute_drip(
+            owner,
+            &ExecuteMsg::ResumeDripPool {
+                token: cw20.clone(),
+            },
+        )
+        .unwrap();
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+    let pool = test_lab.query_drip_pool(cw20.clone()).drip_pool.unwrap();
+    assert_eq!(pool.epoch, 2);
+    assert_eq!(pool.status, PoolStatus::Active);
+
+    let _resp = test_lab.withdraw_tokens(Addr::unchecked(PAR1)).unwrap();
+    assert_eq!(
+        test_lab.query_cw20_balance(PAR1.into()),
+        Uint128::new(2_000)
+    );
+}
+
+#[test]
+fn top_up_drip_pool() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 2u128)
+        .sudo_mint_1000(owner.to_string(), native.clone(), 10u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_002u128)
+        .init_cw20(vec![
+            Cw20Coin {
+                address: drip_addr,
+                amount: Uint128::new(10_000),
+            },
+            Cw20Coin {
+                address: owner.to_string(),
+                amount: Uint128::new(10_000),
+            },
+        ]);
+    let cw20 = test_lab.cw20_address.clone();
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(2_000),
+            },
+            Uint128::new(1_000),
+            2u64,
+            &[],
+        )
+        .unwrap();
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Cw20 {
+                address: cw20.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    for _ in 0..2 {
+        test_lab.advance_blocks(EPOCH);
+        let _resp = test_lab.distribute_shares().unwrap();
+    }
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.status, PoolStatus::Finished);
+
+    let err: ContractError = test_lab
+        .top_up_drip_pool(
+            Addr::unchecked(PAR1),
+            native.clone(),
+            2,
+            &[Coin::new(2_000, native.clone())],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Deposit must cover tokens per epoch for every additional epoch
+    let err: ContractError = test_lab
+        .top_up_drip_pool(
+            owner.clone(),
+            native.clone(),
+            2,
+            &[Coin::new(1_500, native.clone())],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::WrongTokensAmount {
+            tokens_amount: Uint128::new(1_500),
+            total_tokens: Uint128::new(2_000),
+        }
+    );
+
+    // The finished pool drips again
+    let _resp = test_lab
+        .top_up_drip_pool(
+            owner.clone(),
+            native.clone(),
+            2,
+            &[Coin::new(2_000, native.clone())],
+        )
+        .unwrap();
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.status, PoolStatus::Active);
+    assert_eq!(pool.epochs_number, 4);
+    assert_eq!(pool.tokens_per_epoch, Uint128::new(1_000));
+
+    // Cw20 pools are topped up sending tokens
+    let _resp = test_lab
+        .send_cw20(
+            owner,
+            Uint128::new(3_000),
+            to_binary(&ReceiveMsg::TopUpDripPool {
+                additional_epochs: 3,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+    let pool = test_lab.query_drip_pool(cw20.clone()).drip_pool.unwrap();
+    assert_eq!(pool.epochs_number, 13);
+    assert_eq!(pool.drip_token.get_available_amount(), Uint128::new(11_000));
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.epoch, 3);
+
+    let _resp = test_lab.withdraw_tokens(Addr::unchecked(PAR1)).unwrap();
+    assert_eq!(test_lab.query_balance(PAR1.into()), Uint128::new(5_000));
+    assert_eq!(
+        test_lab.query_cw20_balance(PAR1.into()),
+        Uint128::new(3_000)
+    );
+}
+
+#[test]
+fn sponsored_drip_pool() {
+    let mut test_lab = LabBuilder::new()
+        .with_sponsorship(SponsorshipConfig {
+            creation_fee: Decimal::percent(1),
+            min_initial_amount: Uint128::new(5_000),
+        })
+        .build();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    let drip_addr = Addr::unchecked(test_lab.drip_address.clone());
+    let sponsor = Addr::unchecked(SPONSOR);
+    test_lab = test_lab
+        .sudo_mint_1000(SPONSOR.to_string(), native.clone(), 20u128)
+        .init_cw20(vec![Cw20Coin {
+            address: SPONSOR.to_string(),
+            amount: Uint128::new(11_100),
+        }]);
+    let cw20 = test_lab.cw20_address.clone();
+    for token in [native.clone(), cw20.clone()] {
+        let _resp = test_lab
+            .execute_drip(
+                owner.clone(),
+                &ExecuteMsg::AllowDripToken {
+                    token,
+                    min_initial_amount: None,
+                },
+            )
+            .unwrap();
+    }
+
+    let create_msg = |initial_amount: u128| ExecuteMsg::CreateDripPool {
+        token_info: UncheckedDripToken::Native {
+            denom: native.clone(),
+            initial_amount: Uint128::new(initial_amount),
+        },
+        tokens_per_epoch: Uint128::new(initial_amount / 10),
+        epochs_number: 10,
+        vesting: None,
+        early_exit_penalty: None,
+        emission_schedule: None,
+        start_time: None,
+        end_time: None,
+    };
+
+    let err: ContractError = test_lab
+        .app
+        .execute_contract(
+            sponsor.clone(),
+            drip_addr.clone(),
+            &create_msg(1_000),
+            &[Coin::new(1_010, native.clone())],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::DripPoolTooSmall {
+            min_initial_amount: Uint128::new(5_000)
+        }
+    );
+
+    // The creation fee is paid on top of the initial amount
+    let err: ContractError = test_lab
+        .app
+        .execute_contract(
+            sponsor.clone(),
+            drip_addr.clone(),
+            &create_msg(10_000),
+            &[Coin::new(10_000, native.clone())],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::WrongDeposit {
+            expected: Uint128::new(10_100),
+            received: Uint128::new(10_000),
+        }
+    );
+
+    let _resp = test_lab
+        .app
+        .execute_contract(
+            sponsor.clone(),
+            drip_addr.clone(),
+            &create_msg(10_000),
+            &[Coin::new(10_100, native.clone())],
+        )
+        .unwrap();
+    assert_eq!(test_lab.query_balance(owner.to_string()), Uint128::new(100));
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.creator, sponsor);
+
+    // Only the creator can cancel the pool and receives the refund
+    let remove_msg = ExecuteMsg::RemoveDripPool {
+        token: native.clone(),
+    };
+    let err: ContractError = test_lab
+        .execute_drip(owner.clone(), &remove_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let _resp = test_lab.execute_drip(sponsor.clone(), &remove_msg).unwrap();
+    assert_eq!(
+        test_lab.query_balance(SPONSOR.to_string()),
+        Uint128::new(19_900)
+    );
+
+    // Cw20 pools are sponsored sending the tokens
+    let _resp = test_lab
+        .send_cw20(
+            sponsor.clone(),
+            Uint128::new(10_100),
+            to_binary(&ReceiveMsg::CreateDripPool {
+                initial_amount: Uint128::new(10_000),
+                tokens_per_epoch: Uint128::new(1_000),
+                epochs_number: 10,
+                vesting: None,
+                early_exit_penalty: None,
+                emission_schedule: None,
+                start_time: None,
+                end_time: None,
+            })
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        test_lab.query_cw20_balance(owner.to_string()),
+        Uint128::new(100)
+    );
+    let pool = test_lab.query_drip_pool(cw20.clone()).drip_pool.unwrap();
+    assert_eq!(pool.creator, sponsor);
+    assert_eq!(pool.drip_token.get_available_amount(), Uint128::new(10_000));
+
+    // Only the sponsor can top up its pool, since it receives the refund if cancelled
+    let top_up_msg = to_binary(&ReceiveMsg::TopUpDripPool {
+        additional_epochs: 1,
+    })
+    .unwrap();
+    let err: ContractError = test_lab
+        .send_cw20(owner, Uint128::new(100), top_up_msg.clone())
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let _resp = test_lab
+        .send_cw20(sponsor, Uint128::new(1_000), top_up_msg)
+        .unwrap();
+    let pool = test_lab.query_drip_pool(cw20).drip_pool.unwrap();
+    assert_eq!(pool.epochs_number, 11);
+    assert_eq!(pool.drip_token.get_available_amount(), Uint128::new(11_000));
+}
+
+#[test]
+fn allowed_drip_tokens() {
+    let mut test_lab = LabBuilder::new()
+        .with_sponsorship(SponsorshipConfig {
+            creation_fee: Decimal::zero(),
+            min_initial_amount: Uint128::new(5_000),
+        })
+        .build();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    let drip_addr = Addr::unchecked(test_lab.drip_address.clone());
+    let sponsor = Addr::unchecked(SPONSOR);
+    test_lab = test_lab.sudo_mint_1000(SPONSOR.to_string(), native.clone(), 10u128);
+
+    let create_msg = ExecuteMsg::CreateDripPool {
+        token_info: UncheckedDripToken::Native {
+            denom: native.clone(),
+            initial_amount: Uint128::new(10_000),
+        },
+        tokens_per_epoch: Uint128::new(1_000),
+        epochs_number: 10,
+        vesting: None,
+        early_exit_penalty: None,
+        emission_schedule: None,
+        start_time: None,
+        end_time: None,
+    };
+    let allow_msg = |min_initial_amount: Option<u128>| ExecuteMsg::AllowDripToken {
+        token: native.clone(),
+        min_initial_amount: min_initial_amount.map(Uint128::new),
+    };
+
+    let err: ContractError = test_lab
+        .execute_drip(sponsor.clone(), &allow_msg(None))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err: ContractError = test_lab
+        .app
+        .execute_contract(
+            sponsor.clone(),
+            drip_addr.clone(),
+            &create_msg,
+            &[Coin::new(10_000, native.clone())],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::DripTokenNotAllowed {
+            token: native.clone()
+        }
+    );
+
+    // Tokens can require a bigger pool than the global minimum
+    let _resp = test_lab
+        .execute_drip(owner.clone(), &allow_msg(Some(20_000)))
+        .unwrap();
+    assert_eq!(
+        test_lab.query_allowed_drip_tokens().allowed_drip_tokens,
+        vec![AllowedDripToken {
+            token: native.clone(),
+            min_initial_amount: Some(Uint128::new(20_000)),
+        }]
+    );
+    let err: ContractError = test_lab
+        .app
+        .execute_contract(
+            sponsor.clone(),
+            drip_addr.clone(),
+            &create_msg,
+            &[Coin::new(10_000, native.clone())],
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::DripPoolTooSmall {
+            min_initial_amount: Uint128::new(20_000)
+        }
+    );
+
+    let _resp = test_lab
+        .execute_drip(owner.clone(), &allow_msg(None))
+        .unwrap();
+    let _resp = test_lab
+        .app
+        .execute_contract(
+            sponsor,
+            drip_addr,
+            &create_msg,
+            &[Coin::new(10_000, native.clone())],
+        )
+        .unwrap();
+
+    let _resp = test_lab
+        .execute_drip(owner, &ExecuteMsg::DisallowDripToken { token: native })
+        .unwrap();
+    assert!(test_lab
+        .query_allowed_drip_tokens()
+        .allowed_drip_tokens
+        .is_empty());
+}
+
+#[test]
+fn invalid_denom() {
+    let ibc_denom = format!(
+        "ibc/{}",
+        "27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2"
+    );
+    let factory_denom = "factory/creator/udrip".to_string();
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), ibc_denom.clone(), 10u128)
+        .sudo_mint_1000(drip_addr, factory_denom.clone(), 10u128);
+
+    for (denom, reason) in [
+        ("ibc/27394fb092", "hash must be 64 uppercase hex characters"),
+        ("factory/creator", "missing subdenom"),
+        ("factory/creator/", "empty subdenom"),
+        ("1drip", "must start with a letter"),
+        ("u$drip", "contains invalid characters"),
+    ] {
+        let err: ContractError = test_lab
+            .create_drip_pool(
+                UncheckedDripToken::Native {
+                    denom: denom.to_string(),
+                    initial_amount: Uint128::new(10_000),
+                },
+                Uint128::new(1_000),
+                10u64,
+                &[],
+            )
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(
+            err,
+            ContractError::InvalidDenom {
+                denom: denom.to_string(),
+                reason: reason.to_string(),
+            }
+        );
+    }
+
+    for denom in [ibc_denom, factory_denom] {
+        let _resp = test_lab
+            .create_drip_pool(
+                UncheckedDripToken::Native {
+                    denom: denom.clone(),
+                    initial_amount: Uint128::new(10_000),
+                },
+                Uint128::new(1_000),
+                10u64,
+                &[],
+            )
+            .unwrap();
+        // Native metadata are not looked up by default
+        let pool = test_lab.query_drip_pool(denom).drip_pool.unwrap();
+        assert_eq!(pool.metadata, None);
+    }
+}
@@ -0,0 +1,200 @@
+use cosmwasm_std::testing::{MockApi, MockStorage};
+use cosmwasm_std::{
+    from_slice, Addr, Binary, Coin, ContractResult, CosmosMsg, Deps, Empty, Querier, QuerierResult,
+    QuerierWrapper, QueryRequest, SystemError, SystemResult, Uint128,
+};
+use prost::Message;
+
+use crate::helpers::{authz_delegate_msg, query_token_metadata};
+use crate::state::{DripToken, TokenMetadata};
+
+// Reference definitions of the cosmos-sdk messages encoded by hand by the contract
+
+#[derive(Clone, PartialEq, Message)]
+struct ProtoCoin {
+    #[prost(string, tag = "1")]
+    denom: String,
+    #[prost(string, tag = "2")]
+    amount: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct MsgDelegate {
+    #[prost(string, tag = "1")]
+    delegator_address: String,
+    #[prost(string, tag = "2")]
+    validator_address: String,
+    #[prost(message, optional, tag = "3")]
+    amount: Option<ProtoCoin>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Any {
+    #[prost(string, tag = "1")]
+    type_url: String,
+    #[prost(bytes = "vec", tag = "2")]
+    value: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct MsgExec {
+    #[prost(string, tag = "1")]
+    grantee: String,
+    #[prost(message, repeated, tag = "2")]
+    msgs: Vec<Any>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct QueryDenomMetadataRequest {
+    #[prost(string, tag = "1")]
+    denom: String,
+}
+
+fn reference_exec(grantee: &str, delegator: &str, validator: &str, amount: &Coin) -> Vec<u8> {
+    let delegate = MsgDelegate {
+        delegator_address: delegator.to_string(),
+        validator_address: validator.to_string(),
+        amount: Some(ProtoCoin {
+            denom: amount.denom.clone(),
+            amount: amount.amount.to_string(),
+        }),
+    };
+    MsgExec {
+        grantee: grantee.to_string(),
+        msgs: vec![Any {
+            type_url: "/cosmos.staking.v1beta1.MsgDelegate".to_string(),
+            value: delegate.encode_to_vec(),
+        }],
+    }
+    .encode_to_vec()
+}
+
+#[test]
+fn authz_delegate_encoding() {
+    let cases = [
+        (
+            "juno14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9skjuwg8",
+            "juno1t8ehvswxjfn3ejzkjtntcyrqwvmvuknzy3ajxy",
+            "junovaloper1t8ehvswxjfn3ejzkjtntcyrqwvmvuknzmvtaaa",
+            Coin::new(1_000, "ujuno"),
+        ),
+        // Lengths above 127 bytes are encoded with multi-byte varints
+        (
+            "juno14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9skjuwg8",
+            "juno14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9skjuwg8",
+            "junovaloper1t8ehvswxjfn3ejzkjtntcyrqwvmvuknzmvtaaa",
+            Coin::new(
+                u128::MAX,
+                "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+            ),
+        ),
+    ];
+
+    for (grantee, delegator, validator, amount) in cases {
+        let msg = authz_delegate_msg(
+            &Addr::unchecked(grantee),
+            &Addr::unchecked(delegator),
+            validator,
+            &amount,
+        );
+        let expected = reference_exec(grantee, delegator, validator, &amount);
+        match msg {
+            CosmosMsg::Stargate { type_url, value } => {
+                assert_eq!(type_url, "/cosmos.authz.v1beta1.MsgExec");
+                assert_eq!(value.to_vec(), expected);
+            }
+            _ => panic!("expected a stargate message"),
+        }
+    }
+
+    // Known encoding of a MsgExec with a single MsgDelegate
+    let msg = authz_delegate_msg(
+        &Addr::unchecked("grantee"),
+        &Addr::unchecked("delegator"),
+        "validator",
+        &Coin::new(5, "ujuno"),
+    );
+    let value = match msg {
+        CosmosMsg::Stargate { value, .. } => value,
+        _ => panic!("expected a stargate message"),
+    };
+    let mut expected = b"\n\x07grantee\x12I\n#/cosmos.staking.v1beta1.MsgDelegate".to_vec();
+    expected.extend_from_slice(b"\x12\"\n\tdelegator\x12\tvalidator\x1a\n\n\x05ujuno\x12\x015");
+    assert_eq!(value.to_vec(), expected);
+}
+
+/// Answers the bank DenomMetadata Stargate query of a single denom
+struct DenomMetadataQuerier {
+    denom: String,
+    response: Option<&'static str>,
+}
+
+impl Querier for DenomMetadataQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+        let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+        match request {
+            QueryRequest::Stargate { path, data }
+                if path == "/cosmos.bank.v1beta1.Query/DenomMetadata" =>
+            {
+                let request = QueryDenomMetadataRequest::decode(data.as_slice()).unwrap();
+                match self.response {
+                    Some(response) if request.denom == self.denom => {
+                        SystemResult::Ok(ContractResult::Ok(Binary::from(response.as_bytes())))
+                    }
+                    _ => SystemResult::Ok(ContractResult::Err(format!(
+                        "client metadata for denom {}",
+                        request.denom
+                    ))),
+                }
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "non-metadata query".to_string(),
+            }),
+        }
+    }
+}
+
+#[test]
+fn native_token_metadata() {
+    let storage = MockStorage::default();
+    let api = MockApi::default();
+    let drip_token = DripToken::Native {
+        denom: "factory/creator/udrip".to_string(),
+        amount: Uint128::new(1_000),
+    };
+    let token_metadata = |response: Option<&'static str>, denom_metadata: bool| {
+        let querier = DenomMetadataQuerier {
+            denom: "factory/creator/udrip".to_string(),
+            response,
+        };
+        let deps = Deps {
+            storage: &storage,
+            api: &api,
+            querier: QuerierWrapper::new(&querier),
+        };
+        query_token_metadata(deps, &drip_token, denom_metadata)
+    };
+
+    let response = r#"{"metadata":{"description":"","denom_units":[{"denom":"factory/creator/udrip","exponent":0,"aliases":[]},{"denom":"drip","exponent":6,"aliases":[]}],"base":"factory/creator/udrip","display":"drip","name":"","symbol":""}}"#;
+    assert_eq!(
+        token_metadata(Some(response), true).unwrap(),
+        Some(TokenMetadata {
+            display: "drip".to_string(),
+            decimals: 6,
+        })
+    );
+
+    // Not looked up unless enabled
+    assert_eq!(token_metadata(Some(response), false).unwrap(), None);
+
+    // Denom without metadata
+    assert_eq!(token_metadata(None, true).unwrap(), None);
+
+    // Display unit missing from the denom units
+    let response = r#"{"metadata":{"denom_units":[{"denom":"factory/creator/udrip","exponent":0}],"base":"factory/creator/udrip","display":"drip"}}"#;
+    assert_eq!(token_metadata(Some(response), true).unwrap(), None);
+
+    // Response in an unexpected format
+    let response = r#"{"denom_units":[{"denom":"drip","exponent":6}],"display":"drip"}"#;
+    token_metadata(Some(response), true).unwrap_err();
+}
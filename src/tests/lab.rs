@@ -1,20 +1,33 @@
 use anyhow::Result as AnyResult;
 
-use cosmwasm_std::{Addr, Coin, CosmosMsg, Decimal, Empty, StakingMsg, Uint128, Validator};
-use cw20::Cw20Coin;
-pub use cw_multi_test::StakeKeeper;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Order, Response, StakingMsg, StdResult, Uint128, Validator, WasmMsg,
+};
+use cw20::{Cw20Coin, Cw20ReceiveMsg};
 use cw_multi_test::{
     App, AppResponse, BankSudo, Contract, ContractWrapper, Executor, StakingInfo, SudoMsg,
 };
+use cw_storage_plus::{Bound, Item, Map};
+use cw_utils::Expiration;
+
+use crate::state::{PoolStatus, SponsorshipConfig};
 
 use crate::msg::{
-    DripPoolResponse, DripPoolsResponse, DripTokensResponse, ExecuteMsg, InstantiateMsg,
-    ParticipantSharesResponse, ParticipantsResponse, QueryMsg, UncheckedDripToken,
+    AllowedDripTokensResponse, ClaimableResponse, DripPoolResponse, DripPoolsResponse,
+    DripTokensResponse, ExecuteMsg, InstantiateMsg, LinksResponse, LiquidStakingExecuteMsg,
+    LstSourcesResponse, Member, MemberChangedHookMsg, MemberDiff, MemberListResponse,
+    MemberResponse, ParticipantSharesResponse, ParticipantsResponse, PoolShareholdersResponse,
+    QueryMsg, ShareAllowanceResponse, SimulateDistributionResponse, StakedBalanceAtHeightResponse,
+    UncheckedDripToken, UncheckedWeightSource, VestingPositionsResponse,
+    VotingPowerAtHeightResponse, WeightSourceQueryMsg,
 };
 
 pub const PAR1: &str = "participant1";
 pub const PAR2: &str = "participant2";
 pub const PAR3: &str = "participant3";
+pub const SPONSOR: &str = "sponsor";
 
 pub const EPOCH: u64 = 10; // seconds
 pub const MIN_STAKING: Uint128 = Uint128::new(1_000_000);
@@ -25,6 +38,10 @@ pub struct LabBuilder {
     pub contract_owner: String,
     pub native_token_denom: String,
     pub validators: Vec<String>,
+    pub liquid_staking: bool,
+    pub sponsorship: Option<SponsorshipConfig>,
+    pub weight_source: Option<fn(String) -> UncheckedWeightSource>,
+    pub receipt_tokens: bool,
 }
 
 pub struct TestLab {
@@ -33,6 +50,8 @@ pub struct TestLab {
     pub native: String,
     pub drip_address: String,
     pub cw20_address: String,
+    pub liquid_staking_address: Option<String>,
+    pub weight_address: Option<String>,
 }
 
 // Creates a mock drip contract
@@ -41,7 +60,8 @@ pub fn drip_contract() -> Box<dyn Contract<Empty>> {
         crate::contract::execute,
         crate::contract::instantiate,
         crate::contract::query,
-    );
+    )
+    .with_reply(crate::contract::reply);
     Box::new(contract)
 }
 
@@ -55,6 +75,201 @@ pub fn cw20_contract() -> Box<dyn Contract<Empty>> {
     Box::new(contract)
 }
 
+// Messages accepted by the mock receiver contract
+#[cw_serde]
+pub enum ReceiverExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+    Deposit {},
+}
+
+// Creates a mock contract that accepts cw20 and native deposits
+pub fn receiver_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        |_deps: DepsMut,
+         _env: Env,
+         info: MessageInfo,
+         msg: ReceiverExecuteMsg|
+         -> StdResult<Response> {
+            Ok(match msg {
+                ReceiverExecuteMsg::Receive(receive_msg) => Response::new()
+                    .add_attribute("action", "receive")
+                    .add_attribute("from", receive_msg.sender)
+                    .add_attribute("amount", receive_msg.amount),
+                ReceiverExecuteMsg::Deposit {} => Response::new()
+                    .add_attribute("action", "deposit")
+                    .add_attribute("from", info.sender),
+            })
+        },
+        |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |_deps: Deps, _env: Env, _msg: Empty| -> StdResult<Binary> { to_binary(&Empty {}) },
+    );
+    Box::new(contract)
+}
+
+// Creates a mock liquid staking contract delegating bonded funds to the first validator
+pub fn liquid_staking_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        |_deps: DepsMut,
+         _env: Env,
+         info: MessageInfo,
+         msg: LiquidStakingExecuteMsg|
+         -> StdResult<Response> {
+            let LiquidStakingExecuteMsg::Bond { receiver } = msg;
+            Ok(Response::new()
+                .add_message(StakingMsg::Delegate {
+                    validator: "validator1".to_string(),
+                    amount: info.funds[0].clone(),
+                })
+                .add_attribute("action", "bond")
+                .add_attribute("receiver", receiver.unwrap_or(info.sender.to_string())))
+        },
+        |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |_deps: Deps, _env: Env, _msg: Empty| -> StdResult<Binary> { to_binary(&Empty {}) },
+    );
+    Box::new(contract)
+}
+
+// Weights returned by the mock weight source contract
+const WEIGHTS: Map<String, Uint128> = Map::new("weights");
+
+// Contract notified of the weight changes like a cw4 group hook
+const HOOK: Item<String> = Item::new("hook");
+
+// Messages accepted by the mock weight source contract
+#[cw_serde]
+pub enum WeightExecuteMsg {
+    SetWeight { address: String, weight: Uint128 },
+    AddHook { addr: String },
+}
+
+// Creates a mock contract answering the queries of every weight source
+pub fn weight_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        |deps: DepsMut,
+         _env: Env,
+         _info: MessageInfo,
+         msg: WeightExecuteMsg|
+         -> StdResult<Response> {
+            let (address, weight) = match msg {
+                WeightExecuteMsg::SetWeight { address, weight } => (address, weight),
+                WeightExecuteMsg::AddHook { addr } => {
+                    HOOK.save(deps.storage, &addr)?;
+                    return Ok(Response::new());
+                }
+            };
+            let old = WEIGHTS.may_load(deps.storage, address.clone())?;
+            // Zero weight removes the member
+            if weight.is_zero() {
+                WEIGHTS.remove(deps.storage, address.clone());
+            } else {
+                WEIGHTS.save(deps.storage, address.clone(), &weight)?;
+            }
+            let mut res = Response::new();
+            if let Some(hook) = HOOK.may_load(deps.storage)? {
+                let diff = MemberDiff {
+                    key: address,
+                    old: old.map(|weight| weight.u128() as u64),
+                    new: (!weight.is_zero()).then_some(weight.u128() as u64),
+                };
+                res = res.add_message(WasmMsg::Execute {
+                    contract_addr: hook,
+                    msg: to_binary(&ExecuteMsg::MemberChangedHook(MemberChangedHookMsg {
+                        diffs: vec![diff],
+                    }))?,
+                    funds: vec![],
+                });
+            }
+            Ok(res)
+        },
+        |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |deps: Deps, env: Env, msg: WeightSourceQueryMsg| -> StdResult<Binary> {
+            let height = env.block.height;
+            match msg {
+                WeightSourceQueryMsg::VotingPowerAtHeight { address, .. } => {
+                    to_binary(&VotingPowerAtHeightResponse {
+                        power: WEIGHTS.may_load(deps.storage, address)?.unwrap_or_default(),
+                        height,
+                    })
+                }
+                WeightSourceQueryMsg::StakedBalanceAtHeight { address, .. } => {
+                    to_binary(&StakedBalanceAtHeightResponse {
+                        balance: WEIGHTS.may_load(deps.storage, address)?.unwrap_or_default(),
+                        height,
+                    })
+                }
+                WeightSourceQueryMsg::Member { addr, .. } => to_binary(&MemberResponse {
+                    weight: WEIGHTS
+                        .may_load(deps.storage, addr)?
+                        .map(|weight| weight.u128() as u64),
+                }),
+                WeightSourceQueryMsg::ListMembers { start_after, limit } => {
+                    let members = WEIGHTS
+                        .range(
+                            deps.storage,
+                            start_after.map(Bound::exclusive),
+                            None,
+                            Order::Ascending,
+                        )
+                        .take(limit.unwrap_or(10) as usize)
+                        .map(|item| {
+                            let (addr, weight) = item?;
+                            Ok(Member {
+                                addr,
+                                weight: weight.u128() as u64,
+                            })
+                        })
+                        .collect::<StdResult<Vec<_>>>()?;
+                    to_binary(&MemberListResponse { members })
+                }
+            }
+        },
+    );
+    Box::new(contract)
+}
+
+// Exchange rate of the mock liquid staking hub
+const EXCHANGE_RATE: Item<Decimal> = Item::new("exchange_rate");
+
+// Queries accepted by the mock liquid staking hub
+#[cw_serde]
+pub enum LstHubQueryMsg {
+    State {},
+}
+
+// State of the mock liquid staking hub
+#[cw_serde]
+pub struct LstHubStateResponse {
+    pub total_ustake: Uint128,
+    pub exchange_rate: Decimal,
+}
+
+// Creates a mock liquid staking hub exposing its exchange rate
+pub fn lst_hub_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        |_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty| -> StdResult<Response> {
+            Ok(Response::new())
+        },
+        |deps: DepsMut, _env: Env, _info: MessageInfo, rate: Decimal| -> StdResult<Response> {
+            EXCHANGE_RATE.save(deps.storage, &rate)?;
+            Ok(Response::new())
+        },
+        |deps: Deps, _env: Env, msg: LstHubQueryMsg| -> StdResult<Binary> {
+            let LstHubQueryMsg::State {} = msg;
+            to_binary(&LstHubStateResponse {
+                total_ustake: Uint128::zero(),
+                exchange_rate: EXCHANGE_RATE.load(deps.storage)?,
+            })
+        },
+    );
+    Box::new(contract)
+}
+
 // Helper function to create a Validator structure with default values
 fn create_default_validator(validator: &str) -> Validator {
     Validator {
@@ -78,9 +293,40 @@ impl LabBuilder {
                 "validator2".to_string(),
                 "validator3".to_string(),
             ],
+            liquid_staking: false,
+            sponsorship: None,
+            weight_source: None,
+            receipt_tokens: false,
         }
     }
 
+    // Instantiates a mock liquid staking contract used by the drip to restake
+    pub fn with_liquid_staking(mut self) -> Self {
+        self.liquid_staking = true;
+        self
+    }
+
+    // Allows anyone to create drip pools
+    pub fn with_sponsorship(mut self, sponsorship: SponsorshipConfig) -> Self {
+        self.sponsorship = Some(sponsorship);
+        self
+    }
+
+    // Instantiates a mock weight source contract used in place of native staking
+    pub fn with_weight_source(
+        mut self,
+        weight_source: fn(String) -> UncheckedWeightSource,
+    ) -> Self {
+        self.weight_source = Some(weight_source);
+        self
+    }
+
+    // Mints the shares of new drip pools as cw20 receipt tokens
+    pub fn with_receipt_tokens(mut self) -> Self {
+        self.receipt_tokens = true;
+        self
+    }
+
     // Adds to the environment objects and params
     pub fn build(self) -> TestLab {
         // Bootstrapping the mocked blockchain
@@ -88,11 +334,42 @@ impl LabBuilder {
 
         let owner = Addr::unchecked(self.contract_owner.clone());
 
+        let liquid_staking_address = self.liquid_staking.then(|| {
+            let liquid_staking_id = app.store_code(liquid_staking_contract());
+            app.instantiate_contract(
+                liquid_staking_id,
+                owner.clone(),
+                &Empty {},
+                &[],
+                "liquid staking",
+                None,
+            )
+            .unwrap()
+            .to_string()
+        });
+
+        let weight_address = self.weight_source.map(|_| {
+            let weight_id = app.store_code(weight_contract());
+            app.instantiate_contract(weight_id, owner.clone(), &Empty {}, &[], "weight", None)
+                .unwrap()
+                .to_string()
+        });
+
+        let receipt_token_code_id = self.receipt_tokens.then(|| app.store_code(cw20_contract()));
+
         let drip_id = app.store_code(drip_contract());
 
         let init_drip_msg = InstantiateMsg {
             min_staking_amount: MIN_STAKING,
             epoch_duration: EPOCH,
+            liquid_staking: liquid_staking_address.clone(),
+            sponsorship: self.sponsorship,
+            denom_metadata: None,
+            weight_source: self
+                .weight_source
+                .zip(weight_address.clone())
+                .map(|(weight_source, address)| weight_source(address)),
+            receipt_token_code_id,
         };
 
         let drip_addr = app
@@ -133,6 +410,8 @@ impl LabBuilder {
             native: self.native_token_denom,
             drip_address: drip_addr.to_string(),
             cw20_address: "None".to_string(),
+            liquid_staking_address,
+            weight_address,
         }
     }
 }
@@ -166,6 +445,34 @@ impl TestLab {
         self
     }
 
+    pub fn init_lst_hub(&mut self, exchange_rate: Decimal) -> Addr {
+        let lst_hub_id = self.app.store_code(lst_hub_contract());
+        self.app
+            .instantiate_contract(
+                lst_hub_id,
+                Addr::unchecked(self.owner.clone()),
+                &exchange_rate,
+                &[],
+                "lst hub",
+                None,
+            )
+            .unwrap()
+    }
+
+    pub fn init_receiver(&mut self) -> Addr {
+        let receiver_id = self.app.store_code(receiver_contract());
+        self.app
+            .instantiate_contract(
+                receiver_id,
+                Addr::unchecked(self.owner.clone()),
+                &Empty {},
+                &[],
+                "receiver",
+                None,
+            )
+            .unwrap()
+    }
+
     pub fn advance_blocks(&mut self, added_seconds: u64) {
         self.app.update_block(|block| {
             block.height += added_seconds / 5;
@@ -197,15 +504,64 @@ impl TestLab {
         self.app.execute(sender, CosmosMsg::Staking(msg)).unwrap()
     }
 
+    pub fn undelegate(&mut self, sender: Addr, validator: String, amount: Coin) -> AppResponse {
+        let msg = StakingMsg::Undelegate { validator, amount };
+        self.app.execute(sender, CosmosMsg::Staking(msg)).unwrap()
+    }
+
     pub fn query_participants(&self) -> ParticipantsResponse {
+        self.query_participants_page(None, None)
+    }
+
+    // Returns a page of participants
+    pub fn query_participants_page(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> ParticipantsResponse {
         let resp: ParticipantsResponse = self
             .app
             .wrap()
-            .query_wasm_smart(self.drip_address.clone(), &QueryMsg::Participants {})
+            .query_wasm_smart(
+                self.drip_address.clone(),
+                &QueryMsg::Participants { start_after, limit },
+            )
+            .unwrap();
+        resp
+    }
+
+    // Returns a page of the holders of settled shares of a drip pool
+    pub fn query_pool_shareholders(
+        &self,
+        token: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> PoolShareholdersResponse {
+        let resp: PoolShareholdersResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.drip_address.clone(),
+                &QueryMsg::PoolShareholders {
+                    token,
+                    start_after,
+                    limit,
+                },
+            )
             .unwrap();
         resp
     }
 
+    pub fn query_delegation(&self, delegator: String, validator: String) -> Uint128 {
+        let resp = self
+            .app
+            .wrap()
+            .query_delegation(delegator, validator)
+            .unwrap();
+        resp.map(|delegation| delegation.amount.amount)
+            .unwrap_or_default()
+    }
+
     pub fn query_balance(&self, address: String) -> Uint128 {
         let resp = self
             .app
@@ -227,6 +583,75 @@ impl TestLab {
         resp.balance
     }
 
+    // Returns the balance of a drip pool receipt token
+    pub fn query_receipt_balance(&self, receipt_token: &Addr, address: String) -> Uint128 {
+        let resp: cw20::BalanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(receipt_token, &cw20::Cw20QueryMsg::Balance { address })
+            .unwrap();
+        resp.balance
+    }
+
+    // Returns the total supply of a drip pool receipt token
+    pub fn query_receipt_supply(&self, receipt_token: &Addr) -> Uint128 {
+        let resp: cw20::TokenInfoResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(receipt_token, &cw20::Cw20QueryMsg::TokenInfo {})
+            .unwrap();
+        resp.total_supply
+    }
+
+    // Returns the preview of the next distribution
+    pub fn query_simulate_distribution(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> SimulateDistributionResponse {
+        let resp: SimulateDistributionResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.drip_address.clone(),
+                &QueryMsg::SimulateDistribution { start_after, limit },
+            )
+            .unwrap();
+        resp
+    }
+
+    // Returns the tokens claimable by an address in every drip pool
+    pub fn query_claimable(&self, address: String) -> ClaimableResponse {
+        let resp: ClaimableResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.drip_address.clone(), &QueryMsg::Claimable { address })
+            .unwrap();
+        resp
+    }
+
+    // Returns the shares of a drip pool a spender can transfer for an owner
+    pub fn query_share_allowance(
+        &self,
+        owner: &str,
+        spender: &str,
+        token: String,
+    ) -> ShareAllowanceResponse {
+        let resp: ShareAllowanceResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.drip_address.clone(),
+                &QueryMsg::ShareAllowance {
+                    owner: owner.to_string(),
+                    spender: spender.to_string(),
+                    token,
+                },
+            )
+            .unwrap();
+        resp
+    }
+
     // Returns a specific drip pool
     pub fn query_drip_pool(&self, token: String) -> DripPoolResponse {
         let resp: DripPoolResponse = self
@@ -239,10 +664,52 @@ impl TestLab {
 
     // Returns all drip pools
     pub fn query_drip_pools(&self) -> DripPoolsResponse {
+        self.query_drip_pools_by_status(None)
+    }
+
+    // Returns drip pools with the given status
+    pub fn query_drip_pools_by_status(&self, status: Option<PoolStatus>) -> DripPoolsResponse {
         let resp: DripPoolsResponse = self
             .app
             .wrap()
-            .query_wasm_smart(self.drip_address.clone(), &QueryMsg::DripPools {})
+            .query_wasm_smart(
+                self.drip_address.clone(),
+                &QueryMsg::DripPools {
+                    status,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        resp
+    }
+
+    // Returns the links of an address
+    pub fn query_links(&self, address: String) -> LinksResponse {
+        let resp: LinksResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.drip_address.clone(), &QueryMsg::Links { address })
+            .unwrap();
+        resp
+    }
+
+    // Returns the liquid staking tokens counted as native stake
+    pub fn query_lst_sources(&self) -> LstSourcesResponse {
+        let resp: LstSourcesResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.drip_address.clone(), &QueryMsg::LstSources {})
+            .unwrap();
+        resp
+    }
+
+    // Returns the tokens sponsors can distribute
+    pub fn query_allowed_drip_tokens(&self) -> AllowedDripTokensResponse {
+        let resp: AllowedDripTokensResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(self.drip_address.clone(), &QueryMsg::AllowedDripTokens {})
             .unwrap();
         resp
     }
@@ -257,6 +724,18 @@ impl TestLab {
         resp
     }
 
+    pub fn query_vesting_positions(&self, address: String) -> VestingPositionsResponse {
+        let resp: VestingPositionsResponse = self
+            .app
+            .wrap()
+            .query_wasm_smart(
+                self.drip_address.clone(),
+                &QueryMsg::VestingPositions { address },
+            )
+            .unwrap();
+        resp
+    }
+
     pub fn query_participant_shares(&self, participant: String) -> ParticipantSharesResponse {
         let resp: ParticipantSharesResponse = self
             .app
@@ -265,6 +744,8 @@ impl TestLab {
                 self.drip_address.clone(),
                 &QueryMsg::ParticipantShares {
                     address: participant,
+                    start_after: None,
+                    limit: None,
                 },
             )
             .unwrap();
@@ -286,6 +767,11 @@ impl TestLab {
                 token_info,
                 tokens_per_epoch,
                 epochs_number,
+                vesting: None,
+                early_exit_penalty: None,
+                emission_schedule: None,
+                start_time: None,
+                end_time: None,
             },
             funds,
         )
@@ -295,7 +781,33 @@ impl TestLab {
         self.app.execute_contract(
             address,
             Addr::unchecked(self.drip_address.clone()),
-            &ExecuteMsg::WithdrawTokens {},
+            &ExecuteMsg::WithdrawTokens {
+                tokens: None,
+                shares: None,
+                owner: None,
+                recipient: None,
+                restake_to: None,
+            },
+            &[],
+        )
+    }
+
+    pub fn withdraw_tokens_partial(
+        &mut self,
+        address: Addr,
+        tokens: Option<Vec<String>>,
+        shares: Option<Uint128>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            address,
+            Addr::unchecked(self.drip_address.clone()),
+            &ExecuteMsg::WithdrawTokens {
+                tokens,
+                shares,
+                owner: None,
+                recipient: None,
+                restake_to: None,
+            },
             &[],
         )
     }
@@ -326,4 +838,101 @@ impl TestLab {
             &[],
         )
     }
+
+    pub fn sync(&mut self, participant: Addr) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            participant,
+            Addr::unchecked(self.drip_address.clone()),
+            &ExecuteMsg::Sync { address: None },
+            &[],
+        )
+    }
+
+    pub fn authorize(
+        &mut self,
+        owner: Addr,
+        operator: String,
+        expiry: Option<Expiration>,
+        recipient: Option<String>,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            owner,
+            Addr::unchecked(self.drip_address.clone()),
+            &ExecuteMsg::Authorize {
+                operator,
+                expiry,
+                recipient,
+            },
+            &[],
+        )
+    }
+
+    pub fn top_up_drip_pool(
+        &mut self,
+        sender: Addr,
+        token: String,
+        additional_epochs: u64,
+        funds: &[Coin],
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender,
+            Addr::unchecked(self.drip_address.clone()),
+            &ExecuteMsg::TopUpDripPool {
+                token,
+                additional_epochs,
+            },
+            funds,
+        )
+    }
+
+    // Sends cw20 tokens to the drip contract along with a message
+    pub fn send_cw20(
+        &mut self,
+        sender: Addr,
+        amount: Uint128,
+        msg: Binary,
+    ) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender,
+            Addr::unchecked(self.cw20_address.clone()),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: self.drip_address.clone(),
+                amount,
+                msg,
+            },
+            &[],
+        )
+    }
+
+    // Sets the weight of an address in the mock weight source
+    pub fn set_weight(&mut self, address: &str, weight: u128) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            Addr::unchecked(self.owner.clone()),
+            Addr::unchecked(self.weight_address.clone().unwrap()),
+            &WeightExecuteMsg::SetWeight {
+                address: address.to_string(),
+                weight: Uint128::new(weight),
+            },
+            &[],
+        )
+    }
+
+    // Registers the drip contract as hook of the mock weight source
+    pub fn add_weight_hook(&mut self) {
+        self.app
+            .execute_contract(
+                Addr::unchecked(self.owner.clone()),
+                Addr::unchecked(self.weight_address.clone().unwrap()),
+                &WeightExecuteMsg::AddHook {
+                    addr: self.drip_address.clone(),
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    pub fn execute_drip(&mut self, sender: Addr, msg: &ExecuteMsg) -> AnyResult<AppResponse> {
+        self.app
+            .execute_contract(sender, Addr::unchecked(self.drip_address.clone()), msg, &[])
+    }
 }
@@ -1,7 +1,13 @@
-use cosmwasm_std::{Addr, Coin, Uint128};
+use cosmwasm_std::{to_binary, Addr, Coin, Decimal, Event, Uint128};
+use cw20::Cw20Coin;
+use cw_multi_test::Executor;
 
 use crate::{
-    tests::lab::{LabBuilder, MIN_STAKING, PAR1, PAR2, PAR3},
+    msg::{
+        ExecuteMsg, MemberChangedHookMsg, MemberDiff, UncheckedDripToken, UncheckedLstSource,
+        UncheckedLstToken, UncheckedRedemptionRate, UncheckedWeightSource,
+    },
+    tests::lab::{LabBuilder, LstHubQueryMsg, TestLab, EPOCH, MIN_STAKING, PAR1, PAR2, PAR3},
     ContractError,
 };
 
@@ -145,3 +151,455 @@ fn participants() {
     assert_eq!(resp.participants.len(), 2);
     assert_eq!(resp.participants, vec![participant2, participant3]);
 }
+
+#[test]
+fn weight_sources() {
+    let weight_sources: [fn(String) -> UncheckedWeightSource; 3] = [
+        |address| UncheckedWeightSource::DaoVotingModule { address },
+        |address| UncheckedWeightSource::Cw20Stake { address },
+        |address| UncheckedWeightSource::Cw4Group { address },
+    ];
+
+    for weight_source in weight_sources {
+        let mut test_lab = LabBuilder::new().with_weight_source(weight_source).build();
+        let drip_addr = test_lab.drip_address.clone();
+        let native = test_lab.native.clone();
+        test_lab = test_lab.sudo_mint_1000(drip_addr, native.clone(), 10u128);
+
+        test_lab.set_weight(PAR1, 2_000_000).unwrap();
+        test_lab.set_weight(PAR2, 500_000).unwrap();
+
+        // Weight below the minimum does not allow to participate
+        let err: ContractError = test_lab
+            .add_participant(Addr::unchecked(PAR2))
+            .unwrap_err()
+            .downcast()
+            .unwrap();
+        assert_eq!(
+            err,
+            ContractError::MinimumDelegationNotSatisfied {
+                min_staked: MIN_STAKING
+            }
+        );
+
+        let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+        let _resp = test_lab
+            .create_drip_pool(
+                UncheckedDripToken::Native {
+                    denom: native.clone(),
+                    initial_amount: Uint128::new(10_000),
+                },
+                Uint128::new(1_000),
+                10u64,
+                &[],
+            )
+            .unwrap();
+        test_lab.advance_blocks(EPOCH);
+        let _resp = test_lab.distribute_shares().unwrap();
+
+        // Shares are issued according to the source weight
+        let resp = test_lab.query_participant_shares(PAR1.to_string());
+        assert_eq!(resp.shares, vec![(native, Uint128::new(2_000_000))]);
+    }
+}
+
+#[test]
+fn cw4_group_members() {
+    let mut test_lab = LabBuilder::new().build();
+    let err: ContractError = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::SyncGroupMembers {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoCw4Group {});
+
+    let mut test_lab = LabBuilder::new()
+        .with_weight_source(|address| UncheckedWeightSource::Cw4Group { address })
+        .build();
+    test_lab.set_weight(PAR1, 2_000_000).unwrap();
+    test_lab.set_weight(PAR2, 1_000_000).unwrap();
+    test_lab.set_weight(PAR3, 500_000).unwrap();
+
+    // Members are added page by page without calling Participate
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR3),
+            &ExecuteMsg::SyncGroupMembers {
+                start_after: None,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+    let resp = test_lab.query_participants();
+    assert_eq!(
+        resp.participants,
+        vec![Addr::unchecked(PAR1), Addr::unchecked(PAR2)]
+    );
+
+    // Members below the minimum weight are not added
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR3),
+            &ExecuteMsg::SyncGroupMembers {
+                start_after: Some(PAR2.to_string()),
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+    let resp = test_lab.query_participants();
+    assert_eq!(resp.participants.len(), 2);
+
+    // Only the group can notify membership changes
+    let err: ContractError = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR3),
+            &ExecuteMsg::MemberChangedHook(MemberChangedHookMsg {
+                diffs: vec![MemberDiff {
+                    key: PAR3.to_string(),
+                    old: Some(500_000),
+                    new: Some(3_000_000),
+                }],
+            }),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Once subscribed, changes are applied as they happen
+    test_lab.add_weight_hook();
+    test_lab.set_weight(PAR3, 3_000_000).unwrap();
+    test_lab.set_weight(PAR1, 0).unwrap();
+    let resp = test_lab.query_participants();
+    assert_eq!(
+        resp.participants,
+        vec![Addr::unchecked(PAR2), Addr::unchecked(PAR3)]
+    );
+}
+
+#[test]
+fn cw4_linked_members() {
+    let mut test_lab = LabBuilder::new()
+        .with_weight_source(|address| UncheckedWeightSource::Cw4Group { address })
+        .build();
+    test_lab.add_weight_hook();
+    test_lab.set_weight(PAR1, 2_000_000).unwrap();
+
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::LinkAddress {
+                address: PAR2.to_string(),
+            },
+        )
+        .unwrap();
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR2),
+            &ExecuteMsg::ConfirmLink {
+                primary: PAR1.to_string(),
+            },
+        )
+        .unwrap();
+
+    // Weight changes of the linked member are accounted to the primary
+    test_lab.set_weight(PAR2, 1_000_000).unwrap();
+    test_lab.set_weight(PAR1, 2_500_000).unwrap();
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR3),
+            &ExecuteMsg::SyncGroupMembers {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    let resp = test_lab.query_simulate_distribution(None, None);
+    assert_eq!(resp.emitted_shares, Uint128::new(3_500_000));
+    assert_eq!(resp.participants.len(), 1);
+    assert_eq!(resp.participants[0].address, Addr::unchecked(PAR1));
+    assert_eq!(resp.participants[0].stake, Uint128::new(3_500_000));
+    assert!(!resp.participants[0].stale);
+
+    // The primary keeps the linked weight when leaving the group
+    test_lab.set_weight(PAR1, 0).unwrap();
+    let resp = test_lab.query_simulate_distribution(None, None);
+    assert_eq!(resp.emitted_shares, Uint128::new(1_000_000));
+    assert_eq!(resp.participants[0].address, Addr::unchecked(PAR1));
+}
+
+#[test]
+fn lst_stake() {
+    let mut test_lab = LabBuilder::new().build();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(PAR1.to_string(), "stujuno".to_string(), 1_000u128)
+        .init_cw20(vec![Cw20Coin {
+            address: PAR2.to_string(),
+            amount: Uint128::new(800_000),
+        }]);
+    let cw20 = test_lab.cw20_address.clone();
+    let lst_hub = test_lab.init_lst_hub(Decimal::percent(125));
+
+    let add_native_msg = ExecuteMsg::AddLstSource {
+        source: UncheckedLstSource {
+            token: UncheckedLstToken::Native {
+                denom: "stujuno".to_string(),
+            },
+            rate: UncheckedRedemptionRate::Fixed {
+                rate: Decimal::percent(150),
+            },
+        },
+    };
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &add_native_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let err: ContractError = test_lab
+        .add_participant(Addr::unchecked(PAR1))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::MinimumDelegationNotSatisfied {
+            min_staked: MIN_STAKING
+        }
+    );
+
+    // Native LST with a fixed rate
+    let _resp = test_lab
+        .execute_drip(owner.clone(), &add_native_msg)
+        .unwrap();
+    let resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+    resp.assert_event(&Event::new("wasm").add_attribute("stake", "1500000"));
+
+    // Cw20 LST with the rate read from the liquid staking hub
+    let _resp = test_lab
+        .execute_drip(
+            owner,
+            &ExecuteMsg::AddLstSource {
+                source: UncheckedLstSource {
+                    token: UncheckedLstToken::Cw20 {
+                        address: cw20.clone(),
+                    },
+                    rate: UncheckedRedemptionRate::Query {
+                        contract: lst_hub.to_string(),
+                        msg: to_binary(&LstHubQueryMsg::State {}).unwrap(),
+                    },
+                },
+            },
+        )
+        .unwrap();
+    let resp = test_lab.add_participant(Addr::unchecked(PAR2)).unwrap();
+    resp.assert_event(&Event::new("wasm").add_attribute("stake", "1000000"));
+
+    let resp = test_lab.query_lst_sources();
+    assert_eq!(resp.lst_sources.len(), 2);
+}
+
+#[test]
+fn lst_transfer() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), "stujuno".to_string(), 1_000u128);
+
+    let _resp = test_lab
+        .execute_drip(
+            owner,
+            &ExecuteMsg::AddLstSource {
+                source: UncheckedLstSource {
+                    token: UncheckedLstToken::Native {
+                        denom: "stujuno".to_string(),
+                    },
+                    rate: UncheckedRedemptionRate::Fixed {
+                        rate: Decimal::one(),
+                    },
+                },
+            },
+        )
+        .unwrap();
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    // The same liquid staking tokens are accounted for both participants until synced
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+    let transfer = |test_lab: &mut TestLab, from: &str, to: &str| {
+        test_lab
+            .app
+            .send_tokens(
+                Addr::unchecked(from),
+                Addr::unchecked(to),
+                &[Coin::new(1_000_000, "stujuno")],
+            )
+            .unwrap();
+    };
+    transfer(&mut test_lab, PAR1, PAR2);
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR2)).unwrap();
+
+    // but the distribution refreshes every participant and counts them only once
+    let resp = test_lab.query_simulate_distribution(None, None);
+    assert_eq!(resp.emitted_shares, Uint128::new(1_000_000));
+    assert!(resp.participants[0].stale);
+
+    test_lab.advance_blocks(EPOCH);
+    let resp = test_lab.distribute_shares().unwrap();
+    resp.assert_event(&Event::new("wasm").add_attribute("emitted shares per pool", "1000000"));
+
+    let resp = test_lab.query_participants();
+    assert_eq!(resp.participants, vec![Addr::unchecked(PAR2)]);
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![]);
+    let resp = test_lab.query_participant_shares(PAR2.to_string());
+    assert_eq!(resp.shares, vec![(native, Uint128::new(1_000_000))]);
+
+    // Keepers can also refresh the participants page by page
+    transfer(&mut test_lab, PAR2, PAR1);
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+    let resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR3),
+            &ExecuteMsg::SyncParticipants {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    resp.assert_event(
+        &Event::new("wasm")
+            .add_attribute("participants", "2")
+            .add_attribute("removed", "1")
+            .add_attribute("last_participant", PAR2),
+    );
+    let resp = test_lab.query_participants();
+    assert_eq!(resp.participants, vec![Addr::unchecked(PAR1)]);
+}
+
+#[test]
+fn linked_addresses() {
+    let vesting = "vesting_account";
+    let mut test_lab = LabBuilder::new().build();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(vesting.to_string(), native.clone(), 1_000u128)
+        .sudo_mint_1000(PAR2.to_string(), native.clone(), 1_000u128);
+    for delegator in [vesting, PAR2] {
+        _ = test_lab.create_delegation(
+            Addr::unchecked(delegator),
+            "validator1".to_string(),
+            Coin {
+                denom: native.clone(),
+                amount: Uint128::new(1_000_000),
+            },
+        );
+    }
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR2)).unwrap();
+
+    let link_msg = |address: &str| ExecuteMsg::LinkAddress {
+        address: address.to_string(),
+    };
+    let confirm_msg = ExecuteMsg::ConfirmLink {
+        primary: PAR1.to_string(),
+    };
+
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &link_msg(PAR1))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::SelfLink {});
+
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &link_msg(PAR2))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::LinkToParticipant {
+            address: PAR2.to_string()
+        }
+    );
+
+    // The link requires the confirmation of the linked address
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(vesting), &confirm_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoPendingLink {});
+
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &link_msg(vesting))
+        .unwrap();
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(vesting), &confirm_msg)
+        .unwrap();
+
+    // Delegations of the linked address count toward the primary
+    let resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+    resp.assert_event(&Event::new("wasm").add_attribute("stake", "1000000"));
+
+    // An address can be linked only once
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR3), &link_msg(vesting))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::AlreadyLinked {
+            address: vesting.to_string()
+        }
+    );
+    let err: ContractError = test_lab
+        .add_participant(Addr::unchecked(vesting))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::AlreadyLinked {
+            address: vesting.to_string()
+        }
+    );
+
+    let resp = test_lab.query_links(PAR1.to_string());
+    assert_eq!(resp.linked, vec![Addr::unchecked(vesting)]);
+
+    // Without the linked stake the primary is no more eligible
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(vesting),
+            &ExecuteMsg::UnlinkAddress {
+                address: PAR1.to_string(),
+            },
+        )
+        .unwrap();
+    let resp = test_lab.query_participants();
+    assert_eq!(resp.participants, vec![Addr::unchecked(PAR2)]);
+}
@@ -1,9 +1,16 @@
-use cosmwasm_std::{Addr, Coin, Uint128};
-use cw20::Cw20Coin;
+use cosmwasm_std::{to_binary, Addr, Coin, Decimal, Event, Uint128};
+use cw20::{Cw20Coin, Denom};
+use cw_multi_test::Executor;
+use cw_utils::{Duration, Expiration};
 
-use crate::{msg::UncheckedDripToken, ContractError};
+use crate::{
+    helpers::authz_delegate_msg,
+    msg::{ClaimableTokens, ExecuteMsg, ReceiveMsg, UncheckedDripToken},
+    state::PoolStatus,
+    ContractError,
+};
 
-use super::lab::{LabBuilder, EPOCH, PAR1};
+use super::lab::{LabBuilder, ReceiverExecuteMsg, EPOCH, PAR1, PAR2, PAR3};
 
 #[test]
 pub fn withdraw_single() {
@@ -132,3 +139,1250 @@ pub fn withdraw_multiple() {
     let resp = test_lab.query_cw20_balance(PAR1.into());
     assert_eq!(resp, Uint128::new(25_000));
 }
+
+#[test]
+pub fn withdraw_by_token() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .init_cw20(vec![Cw20Coin {
+            address: drip_addr,
+            amount: Uint128::new(1_000_000),
+        }]);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Cw20 {
+                address: test_lab.cw20_address.clone(),
+                initial_amount: Uint128::new(50_000),
+            },
+            Uint128::new(25_000),
+            2u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Withdraw only the cw20 drip pool
+    let cw20_address = test_lab.cw20_address.clone();
+    let _resp = test_lab
+        .withdraw_tokens_partial(Addr::unchecked(PAR1), Some(vec![cw20_address]), None)
+        .unwrap();
+
+    let resp = test_lab.query_cw20_balance(PAR1.into());
+    assert_eq!(resp, Uint128::new(25_000));
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::zero());
+
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(1_000_000))]);
+
+    // No shares left for the cw20 drip pool
+    let cw20_address = test_lab.cw20_address.clone();
+    let err: ContractError = test_lab
+        .withdraw_tokens_partial(Addr::unchecked(PAR1), Some(vec![cw20_address]), None)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoShares {});
+}
+
+#[test]
+pub fn withdraw_partial_shares() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .init_cw20(vec![Cw20Coin {
+            address: drip_addr,
+            amount: Uint128::new(1_000_000),
+        }]);
+    let cw20_address = test_lab.cw20_address.clone();
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Cw20 {
+                address: cw20_address.clone(),
+                initial_amount: Uint128::new(50_000),
+            },
+            Uint128::new(25_000),
+            2u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Shares are burned from a single drip pool
+    let err: ContractError = test_lab
+        .withdraw_tokens_partial(Addr::unchecked(PAR1), None, Some(Uint128::new(250_000)))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::SharesOfManyTokens {});
+    let err: ContractError = test_lab
+        .withdraw_tokens_partial(
+            Addr::unchecked(PAR1),
+            Some(vec![native.clone(), cw20_address.clone()]),
+            Some(Uint128::new(250_000)),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::SharesOfManyTokens {});
+
+    let err: ContractError = test_lab
+        .withdraw_tokens_partial(
+            Addr::unchecked(PAR1),
+            Some(vec![native.clone()]),
+            Some(Uint128::new(2_000_000)),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::NotEnoughShares {
+            token: native.clone(),
+            shares: Uint128::new(1_000_000)
+        }
+    );
+
+    let _resp = test_lab
+        .withdraw_tokens_partial(
+            Addr::unchecked(PAR1),
+            Some(vec![native.clone()]),
+            Some(Uint128::new(250_000)),
+        )
+        .unwrap();
+
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::new(250));
+
+    // Other pools are left untouched
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(
+        resp.shares,
+        vec![
+            (cw20_address, Uint128::new(1_000_000)),
+            (native.clone(), Uint128::new(750_000))
+        ]
+    );
+
+    let resp = test_lab.query_drip_pool(native);
+    let pool = resp.drip_pool.unwrap();
+    assert_eq!(pool.issued_shares, Uint128::new(750_000));
+    assert_eq!(pool.withdrawable_tokens, Uint128::new(750));
+}
+
+#[test]
+pub fn withdraw_to_recipient() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native,
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::WithdrawTokens {
+                tokens: None,
+                shares: None,
+                owner: None,
+                recipient: Some(PAR2.to_string()),
+                restake_to: None,
+            },
+        )
+        .unwrap();
+
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::zero());
+    let resp = test_lab.query_balance(PAR2.into());
+    assert_eq!(resp, Uint128::new(1_000));
+}
+
+#[test]
+pub fn withdraw_on_behalf() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let vault = Addr::unchecked("vault");
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native,
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let withdraw_msg = ExecuteMsg::WithdrawTokens {
+        tokens: None,
+        shares: None,
+        owner: Some(PAR1.to_string()),
+        recipient: Some(vault.to_string()),
+        restake_to: None,
+    };
+
+    let err: ContractError = test_lab
+        .execute_drip(vault.clone(), &withdraw_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Without an allowed recipient the operator can only send tokens to the owner
+    let expiry = Expiration::AtHeight(test_lab.app.block_info().height + 2);
+    let _resp = test_lab
+        .authorize(Addr::unchecked(PAR1), vault.to_string(), Some(expiry), None)
+        .unwrap();
+
+    let err: ContractError = test_lab
+        .execute_drip(vault.clone(), &withdraw_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::UnauthorizedRecipient {
+            recipient: vault.to_string()
+        }
+    );
+
+    let owner_balance = test_lab.query_balance(PAR1.into());
+    let _resp = test_lab
+        .execute_drip(
+            vault.clone(),
+            &ExecuteMsg::WithdrawTokens {
+                tokens: None,
+                shares: None,
+                owner: Some(PAR1.to_string()),
+                recipient: None,
+                restake_to: None,
+            },
+        )
+        .unwrap();
+
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, owner_balance + Uint128::new(1_000));
+
+    // The owner allows the operator to send tokens to the vault
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let expiry = Expiration::AtHeight(test_lab.app.block_info().height + 2);
+    let _resp = test_lab
+        .authorize(
+            Addr::unchecked(PAR1),
+            vault.to_string(),
+            Some(expiry),
+            Some(vault.to_string()),
+        )
+        .unwrap();
+
+    let _resp = test_lab.execute_drip(vault.clone(), &withdraw_msg).unwrap();
+
+    let resp = test_lab.query_balance(vault.to_string());
+    assert_eq!(resp, Uint128::new(1_000));
+
+    // Authorization expires
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let err: ContractError = test_lab
+        .execute_drip(vault, &withdraw_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+pub fn withdraw_and_call() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .init_cw20(vec![Cw20Coin {
+            address: drip_addr,
+            amount: Uint128::new(1_000_000),
+        }]);
+    let receiver = test_lab.init_receiver();
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Cw20 {
+                address: test_lab.cw20_address.clone(),
+                initial_amount: Uint128::new(50_000),
+            },
+            Uint128::new(25_000),
+            2u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Cw20 tokens are delivered with a cw20 Send
+    let resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::WithdrawTokensAndCall {
+                contract: receiver.to_string(),
+                msg: to_binary(&Uint128::zero()).unwrap(),
+                tokens: Some(vec![test_lab.cw20_address.clone()]),
+                shares: None,
+                owner: None,
+            },
+        )
+        .unwrap();
+    assert!(resp.has_event(
+        &Event::new("wasm")
+            .add_attribute("action", "receive")
+            .add_attribute("from", test_lab.drip_address.clone())
+            .add_attribute("amount", "25000")
+    ));
+
+    let resp = test_lab.query_cw20_balance(receiver.to_string());
+    assert_eq!(resp, Uint128::new(25_000));
+
+    // Native tokens are attached as funds
+    let resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::WithdrawTokensAndCall {
+                contract: receiver.to_string(),
+                msg: to_binary(&ReceiverExecuteMsg::Deposit {}).unwrap(),
+                tokens: Some(vec![native]),
+                shares: None,
+                owner: None,
+            },
+        )
+        .unwrap();
+    assert!(resp.has_event(
+        &Event::new("wasm")
+            .add_attribute("action", "deposit")
+            .add_attribute("from", test_lab.drip_address.clone())
+    ));
+
+    let resp = test_lab.query_balance(receiver.to_string());
+    assert_eq!(resp, Uint128::new(1_000));
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::zero());
+}
+
+#[test]
+pub fn withdraw_and_restake() {
+    let mut test_lab = LabBuilder::new().with_liquid_staking().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let liquid_staking = test_lab.liquid_staking_address.clone().unwrap();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .init_cw20(vec![Cw20Coin {
+            address: drip_addr,
+            amount: Uint128::new(1_000_000),
+        }]);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native,
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Cw20 {
+                address: test_lab.cw20_address.clone(),
+                initial_amount: Uint128::new(50_000),
+            },
+            Uint128::new(25_000),
+            2u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let err: ContractError = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::WithdrawTokens {
+                tokens: None,
+                shares: None,
+                owner: None,
+                recipient: None,
+                restake_to: Some("validator9".to_string()),
+            },
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::ValidatorNotFound {
+            validator: "validator9".to_string()
+        }
+    );
+
+    let resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::WithdrawTokens {
+                tokens: None,
+                shares: None,
+                owner: None,
+                recipient: None,
+                restake_to: Some("validator1".to_string()),
+            },
+        )
+        .unwrap();
+    assert!(resp.has_event(
+        &Event::new("wasm")
+            .add_attribute("action", "bond")
+            .add_attribute("receiver", PAR1)
+    ));
+
+    // Native rewards are bonded through the liquid staking contract
+    let resp = test_lab.query_delegation(liquid_staking, "validator1".to_string());
+    assert_eq!(resp, Uint128::new(1_000));
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::zero());
+
+    // Other drip tokens are transferred
+    let resp = test_lab.query_cw20_balance(PAR1.into());
+    assert_eq!(resp, Uint128::new(25_000));
+}
+
+#[test]
+pub fn withdraw_and_restake_authz() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Without liquid staking the tokens are delegated by the drip contract with the
+    // authz grant of the recipient. Multi-test cannot execute the stargate message.
+    let err = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::WithdrawTokens {
+                tokens: None,
+                shares: None,
+                owner: None,
+                recipient: None,
+                restake_to: Some("validator1".to_string()),
+            },
+        )
+        .unwrap_err();
+    let expected = authz_delegate_msg(
+        &Addr::unchecked(drip_addr),
+        &Addr::unchecked(PAR1),
+        "validator1",
+        &Coin::new(1_000, native),
+    );
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Cannot execute {:?}", expected)
+    );
+}
+
+#[test]
+pub fn withdraw_vesting() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let create_msg = |vesting| ExecuteMsg::CreateDripPool {
+        token_info: UncheckedDripToken::Native {
+            denom: native.clone(),
+            initial_amount: Uint128::new(10_000),
+        },
+        tokens_per_epoch: Uint128::new(1_000),
+        epochs_number: 10u64,
+        vesting: Some(vesting),
+        early_exit_penalty: None,
+        emission_schedule: None,
+        start_time: None,
+        end_time: None,
+    };
+
+    let err: ContractError = test_lab
+        .execute_drip(owner.clone(), &create_msg(Duration::Time(0)))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::ZeroVestingDuration {});
+
+    let _resp = test_lab
+        .execute_drip(owner, &create_msg(Duration::Time(100)))
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Vesting tokens cannot be sent to a contract
+    let receiver = test_lab.init_receiver();
+    let err: ContractError = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::WithdrawTokensAndCall {
+                contract: receiver.to_string(),
+                msg: to_binary(&ReceiverExecuteMsg::Deposit {}).unwrap(),
+                tokens: None,
+                shares: None,
+                owner: None,
+            },
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::VestingCall {
+            token: native.clone()
+        }
+    );
+
+    // Vesting tokens cannot be restaked or vest for another address
+    let withdraw_msg =
+        |recipient: Option<&str>, restake_to: Option<&str>| ExecuteMsg::WithdrawTokens {
+            tokens: None,
+            shares: None,
+            owner: None,
+            recipient: recipient.map(str::to_string),
+            restake_to: restake_to.map(str::to_string),
+        };
+    let err: ContractError = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &withdraw_msg(None, Some("validator1")),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::VestingRestake {
+            token: native.clone()
+        }
+    );
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &withdraw_msg(Some(PAR2), None))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::VestingRecipient {
+            token: native.clone()
+        }
+    );
+
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &withdraw_msg(Some(PAR1), None))
+        .unwrap();
+
+    // Withdrawn tokens are locked in a vesting position
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::zero());
+
+    let resp = test_lab.query_vesting_positions(PAR1.to_string());
+    assert_eq!(resp.positions.len(), 1);
+    assert_eq!(resp.positions[0].amount, Uint128::new(1_000));
+    assert_eq!(resp.claimable, vec![(native.clone(), Uint128::zero())]);
+
+    test_lab.advance_blocks(50);
+
+    let resp = test_lab.query_vesting_positions(PAR1.to_string());
+    assert_eq!(resp.claimable, vec![(native.clone(), Uint128::new(500))]);
+
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &ExecuteMsg::ClaimVested {})
+        .unwrap();
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::new(500));
+
+    test_lab.advance_blocks(100);
+
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &ExecuteMsg::ClaimVested {})
+        .unwrap();
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::new(1_000));
+
+    // Fully claimed positions are removed
+    let resp = test_lab.query_vesting_positions(PAR1.to_string());
+    assert!(resp.positions.is_empty());
+
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &ExecuteMsg::ClaimVested {})
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoTokensToWithdraw {});
+}
+
+#[test]
+pub fn withdraw_early_exit_penalty() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    let owner = Addr::unchecked(test_lab.owner.clone());
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .sudo_mint_1000(PAR2.to_string(), native.clone(), 1_000u128);
+
+    for participant in [PAR1, PAR2] {
+        _ = test_lab.create_delegation(
+            Addr::unchecked(participant),
+            "validator1".to_string(),
+            Coin {
+                denom: native.clone(),
+                amount: Uint128::new(1_000_000),
+            },
+        );
+        let _resp = test_lab
+            .add_participant(Addr::unchecked(participant))
+            .unwrap();
+    }
+
+    let create_msg = |penalty| ExecuteMsg::CreateDripPool {
+        token_info: UncheckedDripToken::Native {
+            denom: native.clone(),
+            initial_amount: Uint128::new(2_000),
+        },
+        tokens_per_epoch: Uint128::new(1_000),
+        epochs_number: 2u64,
+        vesting: None,
+        early_exit_penalty: Some(penalty),
+        emission_schedule: None,
+        start_time: None,
+        end_time: None,
+    };
+
+    let err: ContractError = test_lab
+        .execute_drip(owner.clone(), &create_msg(Decimal::one()))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::InvalidEarlyExitPenalty {});
+
+    let _resp = test_lab
+        .execute_drip(owner, &create_msg(Decimal::percent(50)))
+        .unwrap();
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Claimable tokens are computed like the withdrawal
+    let resp = test_lab.query_claimable(PAR1.to_string());
+    assert_eq!(
+        resp.claimable,
+        vec![ClaimableTokens {
+            token: native.clone(),
+            denom: Denom::Native(native.clone()),
+            shares: Uint128::new(1_000_000),
+            amount: Uint128::new(250),
+            penalty: Uint128::new(250),
+            status: PoolStatus::Active,
+        }]
+    );
+
+    // Half of the tokens are forfeited before the final epoch
+    let resp = test_lab.withdraw_tokens(Addr::unchecked(PAR1)).unwrap();
+    assert!(resp.has_event(
+        &Event::new("wasm")
+            .add_attribute("penalty", "250")
+            .add_attribute("amount", "250")
+    ));
+
+    let resp = test_lab.query_balance(PAR1.into());
+    assert_eq!(resp, Uint128::new(250));
+
+    let pool = test_lab.query_drip_pool(native.clone()).drip_pool.unwrap();
+    assert_eq!(pool.withdrawable_tokens, Uint128::new(750));
+    assert_eq!(pool.issued_shares, Uint128::new(1_000_000));
+
+    // No penalty after the final epoch. Forfeited tokens increased the value of
+    // the remaining shares.
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    let resp = test_lab.query_claimable(PAR2.to_string());
+    assert_eq!(resp.claimable[0].amount, Uint128::new(1_166));
+    assert_eq!(resp.claimable[0].penalty, Uint128::zero());
+    assert_eq!(resp.claimable[0].status, PoolStatus::Finished);
+
+    let resp = test_lab.withdraw_tokens(Addr::unchecked(PAR2)).unwrap();
+    assert!(resp.has_event(
+        &Event::new("wasm")
+            .add_attribute("penalty", "0")
+            .add_attribute("amount", "1166")
+    ));
+
+    let resp = test_lab.query_balance(PAR2.into());
+    assert_eq!(resp, Uint128::new(1_166));
+}
+
+#[test]
+fn claimable() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128)
+        .sudo_mint_1000(PAR2.to_string(), native.clone(), 2_000u128)
+        .init_cw20(vec![Cw20Coin {
+            address: drip_addr,
+            amount: Uint128::new(2_000),
+        }]);
+    let cw20 = test_lab.cw20_address.clone();
+
+    for (participant, amount) in [(PAR1, 1_000_000), (PAR2, 2_000_000)] {
+        _ = test_lab.create_delegation(
+            Addr::unchecked(participant),
+            "validator1".to_string(),
+            Coin {
+                denom: native.clone(),
+                amount: Uint128::new(amount),
+            },
+        );
+        let _resp = test_lab
+            .add_participant(Addr::unchecked(participant))
+            .unwrap();
+    }
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Cw20 {
+                address: cw20.clone(),
+                initial_amount: Uint128::new(2_000),
+            },
+            Uint128::new(500),
+            4u64,
+            &[],
+        )
+        .unwrap();
+
+    // Nothing to claim before the first distribution
+    let resp = test_lab.query_claimable(PAR1.to_string());
+    assert_eq!(resp.claimable, vec![]);
+
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+    let _resp = test_lab.sync(Addr::unchecked(PAR1)).unwrap();
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Settled and pending shares are converted with the withdrawal rounding
+    let resp = test_lab.query_claimable(PAR1.to_string());
+    assert_eq!(
+        resp.claimable,
+        vec![
+            ClaimableTokens {
+                token: cw20.clone(),
+                denom: Denom::Cw20(Addr::unchecked(cw20.clone())),
+                shares: Uint128::new(2_000_000),
+                amount: Uint128::new(333),
+                penalty: Uint128::zero(),
+                status: PoolStatus::Active,
+            },
+            ClaimableTokens {
+                token: native.clone(),
+                denom: Denom::Native(native.clone()),
+                shares: Uint128::new(2_000_000),
+                amount: Uint128::new(666),
+                penalty: Uint128::zero(),
+                status: PoolStatus::Active,
+            },
+        ]
+    );
+
+    // Withdrawing pays exactly the claimable tokens
+    let _resp = test_lab.withdraw_tokens(Addr::unchecked(PAR1)).unwrap();
+    assert_eq!(test_lab.query_balance(PAR1.into()), Uint128::new(666));
+    assert_eq!(test_lab.query_cw20_balance(PAR1.into()), Uint128::new(333));
+    let resp = test_lab.query_claimable(PAR1.to_string());
+    assert_eq!(resp.claimable, vec![]);
+
+    let resp = test_lab.query_claimable(PAR2.to_string());
+    assert_eq!(resp.claimable.len(), 2);
+    assert_eq!(resp.claimable[1].shares, Uint128::new(4_000_000));
+    assert_eq!(resp.claimable[1].amount, Uint128::new(1_334));
+}
+
+#[test]
+fn receipt_tokens() {
+    let mut test_lab = LabBuilder::new().with_receipt_tokens().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr.clone(), native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+    let receipt_token = test_lab
+        .query_drip_pool(native.clone())
+        .drip_pool
+        .unwrap()
+        .receipt_token
+        .unwrap();
+
+    // Emitted shares are minted to the drip contract
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+    let issued_shares = Uint128::new(1_000_000);
+    assert_eq!(test_lab.query_receipt_supply(&receipt_token), issued_shares);
+    assert_eq!(
+        test_lab.query_receipt_balance(&receipt_token, drip_addr.clone()),
+        issued_shares
+    );
+
+    let claim_msg = ExecuteMsg::ClaimReceiptTokens {};
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &claim_msg)
+        .unwrap();
+    assert_eq!(
+        test_lab.query_receipt_balance(&receipt_token, PAR1.to_string()),
+        issued_shares
+    );
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert!(resp.shares.is_empty());
+
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &claim_msg)
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::NoShares {});
+
+    // Receipt tokens can be traded
+    let _resp = test_lab
+        .app
+        .execute_contract(
+            Addr::unchecked(PAR1),
+            receipt_token.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: PAR2.to_string(),
+                amount: Uint128::new(400_000),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // Sending receipt tokens burns them and withdraws the associated tokens
+    let _resp = test_lab
+        .app
+        .execute_contract(
+            Addr::unchecked(PAR2),
+            receipt_token.clone(),
+            &cw20::Cw20ExecuteMsg::Send {
+                contract: drip_addr.clone(),
+                amount: Uint128::new(400_000),
+                msg: to_binary(&ReceiveMsg::WithdrawTokens {
+                    recipient: None,
+                    restake_to: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_eq!(test_lab.query_balance(PAR2.into()), Uint128::new(400));
+    let drip_pool = test_lab.query_drip_pool(native).drip_pool.unwrap();
+    assert_eq!(drip_pool.issued_shares, Uint128::new(600_000));
+    assert_eq!(
+        test_lab.query_receipt_supply(&receipt_token),
+        drip_pool.issued_shares
+    );
+
+    // Shares settled on the ledger burn the receipt tokens held by the contract
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+    let _resp = test_lab.withdraw_tokens(Addr::unchecked(PAR1)).unwrap();
+    assert_eq!(test_lab.query_balance(PAR1.into()), Uint128::new(1_000));
+    assert_eq!(
+        test_lab.query_receipt_balance(&receipt_token, drip_addr),
+        Uint128::zero()
+    );
+    assert_eq!(
+        test_lab.query_receipt_supply(&receipt_token),
+        Uint128::new(600_000)
+    );
+
+    // Only receipt tokens can be sent to withdraw
+    test_lab = test_lab.init_cw20(vec![Cw20Coin {
+        address: PAR1.to_string(),
+        amount: Uint128::new(1_000),
+    }]);
+    let cw20_address = test_lab.cw20_address.clone();
+    let err: ContractError = test_lab
+        .send_cw20(
+            Addr::unchecked(PAR1),
+            Uint128::new(1_000),
+            to_binary(&ReceiveMsg::WithdrawTokens {
+                recipient: None,
+                restake_to: None,
+            })
+            .unwrap(),
+        )
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::NotReceiptToken {
+            address: cw20_address
+        }
+    );
+}
+
+#[test]
+fn share_allowances() {
+    let mut test_lab = LabBuilder::new().build();
+    let drip_addr = test_lab.drip_address.clone();
+    let native = test_lab.native.clone();
+    test_lab = test_lab
+        .sudo_mint_1000(drip_addr, native.clone(), 100u128)
+        .sudo_mint_1000(PAR1.to_string(), native.clone(), 1_000u128);
+
+    _ = test_lab.create_delegation(
+        Addr::unchecked(PAR1),
+        "validator1".to_string(),
+        Coin {
+            denom: native.clone(),
+            amount: Uint128::new(1_000_000),
+        },
+    );
+    let _resp = test_lab.add_participant(Addr::unchecked(PAR1)).unwrap();
+    let _resp = test_lab
+        .create_drip_pool(
+            UncheckedDripToken::Native {
+                denom: native.clone(),
+                initial_amount: Uint128::new(10_000),
+            },
+            Uint128::new(1_000),
+            10u64,
+            &[],
+        )
+        .unwrap();
+    test_lab.advance_blocks(EPOCH);
+    let _resp = test_lab.distribute_shares().unwrap();
+
+    // Accrued shares can be sent before being settled
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::SendShares {
+                recipient: PAR2.to_string(),
+                token: native.clone(),
+                amount: Uint128::new(100_000),
+            },
+        )
+        .unwrap();
+    let resp = test_lab.query_participant_shares(PAR2.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(100_000))]);
+
+    let transfer_msg = |amount: u128| ExecuteMsg::TransferSharesFrom {
+        owner: PAR1.to_string(),
+        recipient: PAR3.to_string(),
+        token: native.clone(),
+        amount: Uint128::new(amount),
+    };
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR2), &transfer_msg(100_000))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    let increase_msg =
+        |amount: u128, expires: Option<Expiration>| ExecuteMsg::IncreaseShareAllowance {
+            spender: PAR2.to_string(),
+            token: native.clone(),
+            amount: Uint128::new(amount),
+            expires,
+        };
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &increase_msg(300_000, None))
+        .unwrap();
+    let _resp = test_lab
+        .execute_drip(
+            Addr::unchecked(PAR1),
+            &ExecuteMsg::DecreaseShareAllowance {
+                spender: PAR2.to_string(),
+                token: native.clone(),
+                amount: Uint128::new(100_000),
+                expires: None,
+            },
+        )
+        .unwrap();
+    let resp = test_lab.query_share_allowance(PAR1, PAR2, native.clone());
+    assert_eq!(resp.allowance, Uint128::new(200_000));
+    assert_eq!(resp.expires, Expiration::Never {});
+
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR2), &transfer_msg(300_000))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(
+        err,
+        ContractError::ShareAllowanceExceeded {
+            allowance: Uint128::new(200_000)
+        }
+    );
+
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(PAR2), &transfer_msg(150_000))
+        .unwrap();
+    let resp = test_lab.query_participant_shares(PAR1.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(750_000))]);
+    let resp = test_lab.query_participant_shares(PAR3.to_string());
+    assert_eq!(resp.shares, vec![(native.clone(), Uint128::new(150_000))]);
+    let resp = test_lab.query_share_allowance(PAR1, PAR2, native.clone());
+    assert_eq!(resp.allowance, Uint128::new(50_000));
+
+    // Expired allowances cannot be used
+    let expires = Expiration::AtHeight(test_lab.app.block_info().height + 1);
+    let _resp = test_lab
+        .execute_drip(Addr::unchecked(PAR1), &increase_msg(0, Some(expires)))
+        .unwrap();
+    test_lab.advance_blocks(EPOCH);
+    let err: ContractError = test_lab
+        .execute_drip(Addr::unchecked(PAR2), &transfer_msg(50_000))
+        .unwrap_err()
+        .downcast()
+        .unwrap();
+    assert_eq!(err, ContractError::Expired {});
+    let resp = test_lab.query_share_allowance(PAR1, PAR2, native.clone());
+    assert_eq!(resp.allowance, Uint128::zero());
+    assert_eq!(resp.expires, expires);
+
+    // Received shares are withdrawn like the accrued ones
+    let _resp = test_lab.withdraw_tokens(Addr::unchecked(PAR3)).unwrap();
+    assert_eq!(test_lab.query_balance(PAR3.into()), Uint128::new(150));
+}